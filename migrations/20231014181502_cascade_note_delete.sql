@@ -0,0 +1,6 @@
+-- Deleting a book deletes its notes rather than failing on the foreign key.
+ALTER TABLE note DROP CONSTRAINT fk_book_id;
+ALTER TABLE note ADD CONSTRAINT fk_book_id
+    FOREIGN KEY (book_id)
+    REFERENCES book(id)
+    ON DELETE CASCADE;
@@ -0,0 +1,10 @@
+-- Full-text search over book titles, authors and note text. Titles rank higher than authors.
+ALTER TABLE book ADD COLUMN search tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', title), 'A') || setweight(to_tsvector('english', author), 'B')
+) STORED;
+CREATE INDEX book_search_idx ON book USING GIN (search);
+
+ALTER TABLE note ADD COLUMN search tsvector GENERATED ALWAYS AS (
+    to_tsvector('english', note)
+) STORED;
+CREATE INDEX note_search_idx ON note USING GIN (search);
@@ -0,0 +1,6 @@
+-- Books are deduplicated by ISBN as well as by title and author.
+DROP INDEX book_isbn_10_idx;
+DROP INDEX book_isbn_13_idx;
+
+CREATE UNIQUE INDEX book_isbn_10_key ON book (isbn_10);
+CREATE UNIQUE INDEX book_isbn_13_key ON book (isbn_13);
@@ -0,0 +1,19 @@
+-- A book's link to the provider it was added from moves to its own table, along with the fields the
+-- user overrode when adding it, so that refreshing the book's metadata can leave those alone.
+CREATE TYPE book_field AS ENUM ('title', 'author', 'image_url', 'year', 'pages');
+
+CREATE TABLE book_external_id (
+    book_id INT PRIMARY KEY REFERENCES book (id) ON DELETE CASCADE,
+    provider provider NOT NULL,
+    external_id TEXT NOT NULL,
+    overridden_fields book_field[] NOT NULL DEFAULT '{}'
+);
+
+INSERT INTO book_external_id (book_id, provider, external_id)
+SELECT id, external_provider, external_id FROM book
+WHERE external_provider IS NOT NULL AND external_id IS NOT NULL;
+
+-- Drops the `book_external_id_key` index along with the columns, freeing up its name.
+ALTER TABLE book DROP COLUMN external_provider, DROP COLUMN external_id;
+
+ALTER TABLE book_external_id ADD CONSTRAINT book_external_id_key UNIQUE (provider, external_id);
@@ -0,0 +1,36 @@
+-- Books can credit several people, each with a role, in the order they're credited. `book.author`
+-- stays as the book's primary credit, for display, sorting and deduplication.
+CREATE TYPE author_role AS ENUM ('author', 'translator', 'editor', 'illustrator');
+
+CREATE TABLE author (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE book_author (
+    book_id INT NOT NULL REFERENCES book (id) ON DELETE CASCADE,
+    author_id INT NOT NULL REFERENCES author (id),
+    role author_role NOT NULL DEFAULT 'author',
+    position INT NOT NULL,
+    PRIMARY KEY (book_id, position),
+    UNIQUE (book_id, author_id, role)
+);
+
+CREATE INDEX book_author_author_id_idx ON book_author (author_id);
+
+-- Existing credits like "Jane Doe and John Roe" are split into one author each. Commas are left
+-- alone, since they also appear in names like "Vonnegut, Kurt".
+CREATE TEMPORARY TABLE author_split AS
+SELECT book.id AS book_id, trim(split.name) AS name, split.position - 1 AS position
+FROM book, regexp_split_to_table(book.author, '\s*;\s*|\s+&\s+|\s+and\s+') WITH ORDINALITY AS split(name, position)
+WHERE trim(split.name) <> '';
+
+INSERT INTO author (name)
+SELECT DISTINCT name FROM author_split;
+
+INSERT INTO book_author (book_id, author_id, role, position)
+SELECT author_split.book_id, author.id, 'author', author_split.position
+FROM author_split JOIN author ON author.name = author_split.name
+ON CONFLICT DO NOTHING;
+
+DROP TABLE author_split;
@@ -0,0 +1,15 @@
+-- Books can belong to a series, in reading order. Positions are fractional so that entries like
+-- novellas can go between numbered books, such as 2.5.
+CREATE TABLE series (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    CONSTRAINT series_name_key UNIQUE (name)
+);
+
+CREATE TABLE book_series (
+    book_id INT PRIMARY KEY REFERENCES book (id) ON DELETE CASCADE,
+    series_id INT NOT NULL REFERENCES series (id) ON DELETE CASCADE,
+    position DOUBLE PRECISION NOT NULL
+);
+
+CREATE INDEX book_series_series_id_idx ON book_series (series_id, position);
@@ -0,0 +1,4 @@
+-- Books can be given up on, set aside, or wished for before they're in the library.
+ALTER TYPE status ADD VALUE 'did_not_finish';
+ALTER TYPE status ADD VALUE 'paused';
+ALTER TYPE status ADD VALUE 'wishlist';
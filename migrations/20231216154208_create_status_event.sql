@@ -0,0 +1,11 @@
+-- Every change to a book's reading status and when it happened, so that when a book was started
+-- or finished can be answered. Books already in the library have no history, since when their
+-- status was set isn't known.
+CREATE TABLE status_event (
+    id SERIAL PRIMARY KEY,
+    book_id INT NOT NULL REFERENCES book (id) ON DELETE CASCADE,
+    status status NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX status_event_book_id_idx ON status_event (book_id, changed_at);
@@ -0,0 +1,33 @@
+-- Each read-through of a book is its own reading, so re-reading a book keeps the dates of the
+-- earlier reads. A reading in progress has no outcome, and a book has at most one. Books already
+-- started or finished get one reading, dated from their status history where there is one.
+CREATE TYPE reading_outcome AS ENUM ('finished', 'did_not_finish');
+
+CREATE TABLE reading (
+    id SERIAL PRIMARY KEY,
+    book_id INT NOT NULL REFERENCES book (id) ON DELETE CASCADE,
+    started_at TIMESTAMPTZ,
+    finished_at TIMESTAMPTZ,
+    outcome reading_outcome
+);
+
+CREATE INDEX reading_book_id_idx ON reading (book_id);
+CREATE UNIQUE INDEX reading_in_progress_key ON reading (book_id) WHERE outcome IS NULL;
+
+INSERT INTO reading (book_id, started_at, finished_at, outcome)
+SELECT
+    id,
+    (SELECT MAX(changed_at) FROM status_event WHERE book_id = book.id AND status = 'reading'),
+    CASE WHEN status IN ('read', 'did_not_finish') THEN
+        (SELECT MAX(changed_at) FROM status_event WHERE book_id = book.id AND status = book.status)
+    END,
+    CASE status
+        WHEN 'read' THEN 'finished'::reading_outcome
+        WHEN 'did_not_finish' THEN 'did_not_finish'::reading_outcome
+    END
+FROM book
+WHERE status IN ('reading', 'paused', 'read', 'did_not_finish')
+ORDER BY id;
+
+-- Notes can be taken during a particular reading.
+ALTER TABLE note ADD COLUMN reading_id INT REFERENCES reading (id) ON DELETE SET NULL;
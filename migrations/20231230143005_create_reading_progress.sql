@@ -0,0 +1,10 @@
+-- How far through a book each reading has got. Progress given as a percentage is stored as the
+-- page it works out to.
+CREATE TABLE reading_progress (
+    id SERIAL PRIMARY KEY,
+    reading_id INT NOT NULL REFERENCES reading (id) ON DELETE CASCADE,
+    page INT NOT NULL,
+    logged_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX reading_progress_reading_id_idx ON reading_progress (reading_id, logged_at);
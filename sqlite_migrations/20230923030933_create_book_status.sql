@@ -0,0 +1,4 @@
+-- SQLite has no enum types, so the PostgreSQL `status` enum is a checked TEXT column.
+ALTER TABLE book ADD COLUMN status TEXT DEFAULT 'unread' NOT NULL CHECK (status IN ('unread', 'read', 'reading'));
+
+UPDATE book SET status = 'read' WHERE title = 'Collected Fictions';
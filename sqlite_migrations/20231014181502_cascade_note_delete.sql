@@ -0,0 +1,16 @@
+-- Deleting a book deletes its notes rather than failing on the foreign key. SQLite can't alter a
+-- constraint in place, so the `note` table is rebuilt.
+CREATE TABLE note_new(
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    book_id INTEGER NOT NULL,
+    note TEXT NOT NULL,
+    page INTEGER,
+    CONSTRAINT fk_book_id
+    FOREIGN KEY (book_id)
+    REFERENCES book(id)
+    ON DELETE CASCADE
+);
+
+INSERT INTO note_new (id, book_id, note, page) SELECT id, book_id, note, page FROM note;
+DROP TABLE note;
+ALTER TABLE note_new RENAME TO note;
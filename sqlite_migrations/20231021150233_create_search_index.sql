@@ -0,0 +1,41 @@
+-- Full-text search over book titles, authors and note text, using FTS5 external content tables
+-- kept in sync with triggers.
+CREATE VIRTUAL TABLE book_search USING fts5(
+    title,
+    author,
+    content='book',
+    content_rowid='id',
+    tokenize='porter'
+);
+
+CREATE TRIGGER book_search_insert AFTER INSERT ON book BEGIN
+    INSERT INTO book_search(rowid, title, author) VALUES (new.id, new.title, new.author);
+END;
+CREATE TRIGGER book_search_delete AFTER DELETE ON book BEGIN
+    INSERT INTO book_search(book_search, rowid, title, author) VALUES ('delete', old.id, old.title, old.author);
+END;
+CREATE TRIGGER book_search_update AFTER UPDATE OF title, author ON book BEGIN
+    INSERT INTO book_search(book_search, rowid, title, author) VALUES ('delete', old.id, old.title, old.author);
+    INSERT INTO book_search(rowid, title, author) VALUES (new.id, new.title, new.author);
+END;
+
+CREATE VIRTUAL TABLE note_search USING fts5(
+    note,
+    content='note',
+    content_rowid='id',
+    tokenize='porter'
+);
+
+CREATE TRIGGER note_search_insert AFTER INSERT ON note BEGIN
+    INSERT INTO note_search(rowid, note) VALUES (new.id, new.note);
+END;
+CREATE TRIGGER note_search_delete AFTER DELETE ON note BEGIN
+    INSERT INTO note_search(note_search, rowid, note) VALUES ('delete', old.id, old.note);
+END;
+CREATE TRIGGER note_search_update AFTER UPDATE OF note ON note BEGIN
+    INSERT INTO note_search(note_search, rowid, note) VALUES ('delete', old.id, old.note);
+    INSERT INTO note_search(rowid, note) VALUES (new.id, new.note);
+END;
+
+INSERT INTO book_search(book_search) VALUES ('rebuild');
+INSERT INTO note_search(note_search) VALUES ('rebuild');
@@ -0,0 +1,11 @@
+-- Books added from a metadata provider remember where they came from, and books can have ISBNs,
+-- so that search results can be matched up with books that are already in the library. SQLite has
+-- no enum types, so the PostgreSQL `provider` enum is a checked TEXT column.
+ALTER TABLE book ADD COLUMN external_provider TEXT CHECK (external_provider IN ('google_books', 'open_library'));
+ALTER TABLE book ADD COLUMN external_id TEXT;
+ALTER TABLE book ADD COLUMN isbn_10 TEXT;
+ALTER TABLE book ADD COLUMN isbn_13 TEXT;
+
+CREATE UNIQUE INDEX book_external_id_key ON book (external_provider, external_id);
+CREATE INDEX book_isbn_10_idx ON book (isbn_10);
+CREATE INDEX book_isbn_13_idx ON book (isbn_13);
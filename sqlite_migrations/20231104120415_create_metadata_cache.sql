@@ -0,0 +1,7 @@
+-- Metadata provider responses, keyed by request, so that repeated searches and lookups survive a
+-- restart without using up the providers' quotas. `expires_at` is in seconds since the Unix epoch.
+CREATE TABLE metadata_cache (
+    key TEXT PRIMARY KEY,
+    response TEXT NOT NULL,
+    expires_at INTEGER NOT NULL
+);
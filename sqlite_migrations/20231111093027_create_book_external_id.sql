@@ -0,0 +1,19 @@
+-- A book's link to the provider it was added from moves to its own table, along with the fields the
+-- user overrode when adding it, so that refreshing the book's metadata can leave those alone.
+-- `overridden_fields` is a JSON array of field names, e.g. `["title", "image_url"]`.
+CREATE TABLE book_external_id (
+    book_id INTEGER PRIMARY KEY REFERENCES book (id) ON DELETE CASCADE,
+    provider TEXT NOT NULL CHECK (provider IN ('google_books', 'open_library')),
+    external_id TEXT NOT NULL,
+    overridden_fields TEXT NOT NULL DEFAULT '[]'
+);
+
+INSERT INTO book_external_id (book_id, provider, external_id)
+SELECT id, external_provider, external_id FROM book
+WHERE external_provider IS NOT NULL AND external_id IS NOT NULL;
+
+DROP INDEX book_external_id_key;
+ALTER TABLE book DROP COLUMN external_provider;
+ALTER TABLE book DROP COLUMN external_id;
+
+CREATE UNIQUE INDEX book_external_id_key ON book_external_id (provider, external_id);
@@ -0,0 +1,38 @@
+-- Books can credit several people, each with a role, in the order they're credited. `book.author`
+-- stays as the book's primary credit, for display, sorting and deduplication. SQLite has no enum
+-- types, so the PostgreSQL `author_role` enum is a checked TEXT column.
+CREATE TABLE author (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE book_author (
+    book_id INTEGER NOT NULL REFERENCES book (id) ON DELETE CASCADE,
+    author_id INTEGER NOT NULL REFERENCES author (id),
+    role TEXT NOT NULL DEFAULT 'author' CHECK (role IN ('author', 'translator', 'editor', 'illustrator')),
+    position INTEGER NOT NULL,
+    PRIMARY KEY (book_id, position),
+    UNIQUE (book_id, author_id, role)
+);
+
+CREATE INDEX book_author_author_id_idx ON book_author (author_id);
+
+-- Existing credits like "Jane Doe and John Roe" are split into one author each. Commas are left
+-- alone, since they also appear in names like "Vonnegut, Kurt".
+CREATE TEMPORARY TABLE author_split AS
+WITH RECURSIVE split(book_id, rest, name, position) AS (
+    SELECT id, replace(replace(author, ' & ', ';'), ' and ', ';') || ';', NULL, -1 FROM book
+    UNION ALL
+    SELECT book_id, substr(rest, instr(rest, ';') + 1), trim(substr(rest, 1, instr(rest, ';') - 1)), position + 1
+    FROM split WHERE rest <> ''
+)
+SELECT book_id, name, position FROM split WHERE name IS NOT NULL AND name <> '';
+
+INSERT INTO author (name)
+SELECT DISTINCT name FROM author_split;
+
+INSERT OR IGNORE INTO book_author (book_id, author_id, role, position)
+SELECT author_split.book_id, author.id, 'author', author_split.position
+FROM author_split JOIN author ON author.name = author_split.name;
+
+DROP TABLE author_split;
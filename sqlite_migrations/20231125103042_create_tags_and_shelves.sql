@@ -0,0 +1,23 @@
+-- Free-form tags, and named shelves that keep their books in a manual order.
+CREATE TABLE book_tag (
+    book_id INTEGER NOT NULL REFERENCES book (id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (book_id, tag)
+);
+
+CREATE INDEX book_tag_tag_idx ON book_tag (tag);
+
+CREATE TABLE shelf (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+
+-- `position` orders the books within a shelf, starting from 0.
+CREATE TABLE shelf_book (
+    shelf_id INTEGER NOT NULL REFERENCES shelf (id) ON DELETE CASCADE,
+    book_id INTEGER NOT NULL REFERENCES book (id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    PRIMARY KEY (shelf_id, book_id)
+);
+
+CREATE INDEX shelf_book_book_id_idx ON shelf_book (book_id);
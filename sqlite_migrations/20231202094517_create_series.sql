@@ -0,0 +1,14 @@
+-- Books can belong to a series, in reading order. Positions are fractional so that entries like
+-- novellas can go between numbered books, such as 2.5.
+CREATE TABLE series (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE book_series (
+    book_id INTEGER PRIMARY KEY REFERENCES book (id) ON DELETE CASCADE,
+    series_id INTEGER NOT NULL REFERENCES series (id) ON DELETE CASCADE,
+    position REAL NOT NULL
+);
+
+CREATE INDEX book_series_series_id_idx ON book_series (series_id, position);
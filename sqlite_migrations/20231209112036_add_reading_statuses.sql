@@ -0,0 +1,7 @@
+-- Books can be given up on, set aside, or wished for before they're in the library. SQLite can't
+-- alter a CHECK constraint, and rebuilding `book` would cascade deletes to the tables referencing
+-- it, so the status moves to a new column instead.
+ALTER TABLE book ADD COLUMN new_status TEXT DEFAULT 'unread' NOT NULL CHECK (new_status IN ('unread', 'read', 'reading', 'did_not_finish', 'paused', 'wishlist'));
+UPDATE book SET new_status = status;
+ALTER TABLE book DROP COLUMN status;
+ALTER TABLE book RENAME COLUMN new_status TO status;
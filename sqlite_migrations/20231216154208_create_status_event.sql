@@ -0,0 +1,12 @@
+-- Every change to a book's reading status and when it happened, so that when a book was started
+-- or finished can be answered. Books already in the library have no history, since when their
+-- status was set isn't known. `changed_at` is an RFC 3339 timestamp in UTC, which sorts in time
+-- order as text.
+CREATE TABLE status_event (
+    id INTEGER PRIMARY KEY,
+    book_id INTEGER NOT NULL REFERENCES book (id) ON DELETE CASCADE,
+    status TEXT NOT NULL CHECK (status IN ('unread', 'read', 'reading', 'did_not_finish', 'paused', 'wishlist')),
+    changed_at TEXT NOT NULL
+);
+
+CREATE INDEX status_event_book_id_idx ON status_event (book_id, changed_at);
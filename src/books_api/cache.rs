@@ -0,0 +1,220 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use lru::LruCache;
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::{debug, warn};
+
+use super::BooksApiError;
+use crate::graphql::MetadataCacheStats;
+use crate::repository::Storage;
+
+/// Caches metadata provider responses by request, so that repeating a search or lookup during a
+/// cataloging session doesn't use up the provider's quota.
+///
+/// Responses are kept in an in-process LRU cache, and optionally in the `metadata_cache` table so
+/// that they outlive the process. Both expire after the same TTL. Errors are never cached.
+pub struct MetadataCache {
+    entries: Mutex<LruCache<String, CachedResponse>>,
+    ttl: Duration,
+    store: Option<Storage>,
+    hits: AtomicU64,
+    persistent_hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+struct CachedResponse {
+    /// The response serialized as JSON, so that one cache can hold every type of response
+    body: String,
+    expires_at: Instant,
+}
+
+impl MetadataCache {
+    /// Creates a cache holding up to `size` responses in process, where a size of 0 turns the
+    /// in-process cache off. Responses are also persisted to `store` if one is given.
+    pub fn new(size: usize, ttl: Duration, store: Option<Storage>) -> Self {
+        Self {
+            entries: Mutex::new(LruCache::new(size)),
+            ttl,
+            store,
+            hits: AtomicU64::new(0),
+            persistent_hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the cached response for `key`, or awaits `fetch` and caches its response. With
+    /// `bypass` set the cache isn't read, but the fresh response still replaces the cached one.
+    pub async fn get_or_fetch<T, F>(
+        &self,
+        key: String,
+        bypass: bool,
+        fetch: F,
+    ) -> Result<T, BooksApiError>
+    where
+        T: Serialize + DeserializeOwned,
+        F: Future<Output = Result<T, BooksApiError>>,
+    {
+        if !bypass {
+            if let Some(response) = self.get(&key).await {
+                return Ok(response);
+            }
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let response = fetch.await?;
+        self.put(key, &response).await;
+        Ok(response)
+    }
+
+    /// The hit and miss counts since the app started, and the number of responses in process.
+    pub fn stats(&self) -> MetadataCacheStats {
+        MetadataCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            persistent_hits: self.persistent_hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.lock().len(),
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let body = {
+            let mut entries = self.lock();
+            match entries.get(key) {
+                Some(entry) if entry.expires_at > Instant::now() => Some(entry.body.clone()),
+                Some(_) => {
+                    entries.pop(key);
+                    None
+                }
+                None => None,
+            }
+        };
+        if let Some(response) = body.and_then(|body| serde_json::from_str(&body).ok()) {
+            debug!("Metadata cache hit for {key}");
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Some(response);
+        }
+
+        // A failing store shouldn't fail the request, which can still go to the provider.
+        let body = match &self.store {
+            Some(store) => match store.get_cached_response(key, unix_time()).await {
+                Ok(body) => body?,
+                Err(error) => {
+                    warn!("Could not read the metadata cache: {error}");
+                    return None;
+                }
+            },
+            None => return None,
+        };
+        let response = serde_json::from_str(&body).ok()?;
+        debug!("Persistent metadata cache hit for {key}");
+        self.persistent_hits.fetch_add(1, Ordering::Relaxed);
+        self.insert(key.to_string(), body);
+        Some(response)
+    }
+
+    async fn put<T: Serialize>(&self, key: String, response: &T) {
+        let Ok(body) = serde_json::to_string(response) else {
+            return;
+        };
+
+        if let Some(store) = &self.store {
+            let expires_at = unix_time() + self.ttl.as_secs() as i64;
+            if let Err(error) = store.put_cached_response(&key, &body, expires_at).await {
+                warn!("Could not write to the metadata cache: {error}");
+            }
+        }
+        self.insert(key, body);
+    }
+
+    fn insert(&self, key: String, body: String) {
+        let expires_at = Instant::now() + self.ttl;
+        self.lock().put(key, CachedResponse { body, expires_at });
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruCache<String, CachedResponse>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The current time in seconds since the Unix epoch, which is how the `metadata_cache` table
+/// stores expiry times.
+fn unix_time() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::repository::MemoryBookRepository;
+
+    /// Gets `key` from the cache, where a miss "fetches" `value`.
+    async fn get(cache: &MetadataCache, key: &str, bypass: bool, value: i32) -> i32 {
+        cache
+            .get_or_fetch(key.to_string(), bypass, async move { Ok(value) })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_get_or_fetch() {
+        let cache = MetadataCache::new(1, Duration::from_secs(60), None);
+
+        assert_eq!(1, get(&cache, "a", false, 1).await);
+        assert_eq!(1, get(&cache, "a", false, 2).await);
+        // Bypassing the cache fetches a fresh response, which replaces the cached one.
+        assert_eq!(3, get(&cache, "a", true, 3).await);
+        assert_eq!(3, get(&cache, "a", false, 4).await);
+        // The least recently used response is evicted once the cache is full.
+        assert_eq!(5, get(&cache, "b", false, 5).await);
+        assert_eq!(6, get(&cache, "a", false, 6).await);
+
+        let stats = cache.stats();
+        assert_eq!(2, stats.hits);
+        assert_eq!(4, stats.misses);
+        assert_eq!(1, stats.entries);
+    }
+
+    #[tokio::test]
+    async fn test_errors_and_expired_responses_are_not_cached() {
+        let cache = MetadataCache::new(10, Duration::ZERO, None);
+
+        let error = cache
+            .get_or_fetch("a".into(), false, async {
+                Err::<i32, _>(BooksApiError::RateLimited { retry_after: None })
+            })
+            .await;
+        assert!(error.is_err());
+        assert_eq!(1, get(&cache, "a", false, 1).await);
+        assert_eq!(2, get(&cache, "a", false, 2).await);
+        assert_eq!(0, cache.stats().hits);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_cache() {
+        let store: Storage = Arc::new(MemoryBookRepository::new());
+        let ttl = Duration::from_secs(60);
+
+        let cache = MetadataCache::new(10, ttl, Some(store.clone()));
+        get(&cache, "a", false, 1).await;
+
+        // A new cache, like after a restart, finds the response in the store.
+        let cache = MetadataCache::new(10, ttl, Some(store));
+        assert_eq!(1, get(&cache, "a", false, 2).await);
+        assert_eq!(1, get(&cache, "a", false, 3).await);
+
+        let stats = cache.stats();
+        assert_eq!(1, stats.persistent_hits);
+        assert_eq!(1, stats.hits);
+        assert_eq!(0, stats.misses);
+    }
+}
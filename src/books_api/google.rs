@@ -0,0 +1,283 @@
+use async_trait::async_trait;
+use reqwest::{Client, Url};
+use serde::Deserialize;
+
+use super::{fetch_json, parse_year, BooksApiError, MetadataProvider};
+use crate::graphql::{ExternalProvider, SearchInput, SearchOrder, SearchResult, SearchResultPage};
+
+/// The default URL of the volumes API, which `GoogleBooks::new` can be pointed elsewhere from.
+pub const API_BASE_URL: &str = "https://www.googleapis.com/books/v1/volumes";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiResponse {
+    #[serde(default)]
+    total_items: i32,
+    /// Absent when nothing matched the search
+    #[serde(default)]
+    items: Vec<ApiItem>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiItem {
+    id: String,
+    volume_info: Volume,
+}
+
+impl ApiItem {
+    /// Serializes an `ApiItem` to our `SearchResult` struct.
+    fn into_search_result(self) -> SearchResult {
+        let info = self.volume_info;
+
+        SearchResult {
+            id: self.id,
+            provider: ExternalProvider::GoogleBooks,
+            title: info.title,
+            authors: info.authors,
+            pages: info.page_count.unwrap_or_default(),
+            year: info.published_date.map(parse_year).unwrap_or_default(),
+            image_url: info.image_links.and_then(|links| links.thumbnail),
+            isbn_10: industry_identifier(&info.industry_identifiers, "ISBN_10"),
+            isbn_13: industry_identifier(&info.industry_identifiers, "ISBN_13"),
+        }
+    }
+}
+
+/// The parts of a volume's `volumeInfo` that we use. Only the title is always present.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Volume {
+    title: String,
+    #[serde(default)]
+    authors: Vec<String>,
+    page_count: Option<i32>,
+    published_date: Option<String>,
+    image_links: Option<ImageLinks>,
+    #[serde(default)]
+    industry_identifiers: Vec<IndustryIdentifier>,
+}
+
+#[derive(Deserialize)]
+struct IndustryIdentifier {
+    #[serde(rename = "type")]
+    kind: String,
+    identifier: String,
+}
+
+/// Returns the identifier of the given type, e.g. `ISBN_13`, if the volume has one.
+fn industry_identifier(identifiers: &[IndustryIdentifier], kind: &str) -> Option<String> {
+    identifiers
+        .iter()
+        .find(|identifier| identifier.kind == kind)
+        .map(|identifier| identifier.identifier.clone())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImageLinks {
+    thumbnail: Option<String>,
+}
+
+/// Metadata provider backed by the Google Books API.
+pub struct GoogleBooks {
+    client: Client,
+    api_key: Option<String>,
+    base_url: String,
+}
+
+impl GoogleBooks {
+    /// Creates a provider that sends requests to the volumes API at `base_url`, normally
+    /// `API_BASE_URL`.
+    pub fn new(api_key: Option<String>, base_url: String) -> Self {
+        Self {
+            client: Client::new(),
+            api_key,
+            base_url,
+        }
+    }
+
+    fn api_key(&self) -> Result<&str, BooksApiError> {
+        self.api_key.as_deref().ok_or_else(|| {
+            BooksApiError::NotConfigured("Google Books API key is not configured.".to_string())
+        })
+    }
+}
+
+#[async_trait]
+impl MetadataProvider for GoogleBooks {
+    async fn search(&self, input: SearchInput) -> Result<SearchResultPage, BooksApiError> {
+        let mut params = vec![
+            ("q", query_string(&input)),
+            ("key", self.api_key()?.to_string()),
+            ("startIndex", input.start_index.to_string()),
+            ("maxResults", input.page_size.to_string()),
+            ("printType", "books".to_string()),
+            (
+                "orderBy",
+                match input.order_by {
+                    SearchOrder::Relevance => "relevance",
+                    SearchOrder::Newest => "newest",
+                }
+                .to_string(),
+            ),
+        ];
+        if let Some(language) = input.lang_restrict {
+            params.push(("langRestrict", language));
+        }
+        let request = self.client.get(&self.base_url).query(&params);
+
+        let body =
+            fetch_json::<ApiResponse>(request)
+                .await?
+                .ok_or_else(|| BooksApiError::Upstream {
+                    status: Some(404),
+                    message: "the search endpoint was not found".to_string(),
+                })?;
+
+        Ok(SearchResultPage {
+            total_items: body.total_items,
+            start_index: input.start_index,
+            items: search_results(body),
+        })
+    }
+
+    async fn get_by_id(&self, volume_id: String) -> Result<SearchResult, BooksApiError> {
+        let mut url = Url::parse(&self.base_url).map_err(|error| {
+            BooksApiError::NotConfigured(format!("The Google Books API URL is invalid: {error}"))
+        })?;
+        url.path_segments_mut()
+            .map_err(|_| {
+                BooksApiError::NotConfigured("The Google Books API URL is invalid.".to_string())
+            })?
+            .push(&volume_id);
+        let request = self.client.get(url).query(&[("key", self.api_key()?)]);
+
+        let item = fetch_json::<ApiItem>(request)
+            .await?
+            .ok_or(BooksApiError::NotFound {
+                external_id: volume_id,
+            })?;
+
+        Ok(item.into_search_result())
+    }
+}
+
+/// Builds a Google Books query, e.g. `pynchon intitle:"gravity's rainbow"`, from the free text and
+/// qualifiers in `input`.
+fn query_string(input: &SearchInput) -> String {
+    let mut terms: Vec<String> = input.text().map(str::to_string).into_iter().collect();
+
+    for (name, value) in input.qualifiers() {
+        // Quotes can't be escaped in the query syntax, so they're dropped from qualifier values.
+        let value = value.replace('"', "");
+        if value.contains(char::is_whitespace) {
+            terms.push(format!("{name}:\"{value}\""));
+        } else {
+            terms.push(format!("{name}:{value}"));
+        }
+    }
+
+    terms.join(" ")
+}
+
+fn search_results(body: ApiResponse) -> Vec<SearchResult> {
+    body.items
+        .into_iter()
+        .map(|item| item.into_search_result())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse_json;
+    use super::*;
+
+    #[test]
+    fn test_search_fixture() {
+        let body: ApiResponse = parse_json(include_str!(
+            "../../tests/fixtures/google_books/search.json"
+        ))
+        .unwrap();
+        assert_eq!(412, body.total_items);
+        let results = search_results(body);
+
+        assert_eq!(3, results.len());
+
+        assert_eq!("4CXGDwAAQBAJ", results[0].id);
+        assert_eq!("Gravity's Rainbow", results[0].title);
+        assert_eq!(vec!["Thomas Pynchon".to_string()], results[0].authors);
+        assert_eq!(776, results[0].pages);
+        assert_eq!(1973, results[0].year);
+        assert!(results[0].image_url.is_some());
+
+        // A volume without authors, a published date, a page count or images.
+        assert_eq!("Gravity's Rainbow: A Reader's Guide", results[1].title);
+        assert!(results[1].authors.is_empty());
+        assert_eq!(0, results[1].pages);
+        assert_eq!(0, results[1].year);
+        assert_eq!(None, results[1].image_url);
+
+        // A published date that is only a year, and images without a full-size thumbnail.
+        assert_eq!(2012, results[2].year);
+        assert_eq!(None, results[2].image_url);
+    }
+
+    #[test]
+    fn test_empty_search_fixture() {
+        let body: ApiResponse = parse_json(include_str!(
+            "../../tests/fixtures/google_books/search_empty.json"
+        ))
+        .unwrap();
+
+        assert_eq!(0, body.total_items);
+        assert!(search_results(body).is_empty());
+    }
+
+    #[test]
+    fn test_query_string() {
+        let mut input = SearchInput::from_query("  pynchon ".to_string());
+        assert_eq!("pynchon", query_string(&input));
+
+        input.intitle = Some("gravity's \"rainbow\"".to_string());
+        input.isbn = Some("9780143039945".to_string());
+        input.subject = Some("  ".to_string());
+        assert_eq!(
+            "pynchon intitle:\"gravity's rainbow\" isbn:9780143039945",
+            query_string(&input)
+        );
+
+        input.query = None;
+        assert_eq!(
+            "intitle:\"gravity's rainbow\" isbn:9780143039945",
+            query_string(&input)
+        );
+    }
+
+    #[test]
+    fn test_volume_fixture() {
+        let item: ApiItem = parse_json(include_str!(
+            "../../tests/fixtures/google_books/volume.json"
+        ))
+        .unwrap();
+        let result = item.into_search_result();
+
+        assert_eq!("zyTCAlFPjgYC", result.id);
+        assert_eq!("The Google Story", result.title);
+        assert_eq!(
+            vec!["David A. Vise".to_string(), "Mark Malseed".to_string()],
+            result.authors
+        );
+        assert_eq!(207, result.pages);
+        assert_eq!(2005, result.year);
+        assert_eq!(Some("055380457X".to_string()), result.isbn_10);
+        assert_eq!(Some("9780553804577".to_string()), result.isbn_13);
+    }
+
+    #[test]
+    fn test_malformed_payload() {
+        let result = parse_json::<ApiResponse>(r#"{"items": [{"id": "abc"}]}"#);
+
+        assert!(matches!(result, Err(BooksApiError::MalformedPayload(_))));
+    }
+}
@@ -0,0 +1,332 @@
+use std::{fmt::Display, sync::Arc};
+
+use async_graphql::ErrorExtensions;
+use async_trait::async_trait;
+use reqwest::{header::RETRY_AFTER, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    graphql::{ExternalProvider, MetadataCacheStats, SearchInput, SearchResult, SearchResultPage},
+    isbn::Isbn,
+};
+
+mod cache;
+mod google;
+mod open_library;
+
+pub use cache::MetadataCache;
+pub use google::{GoogleBooks, API_BASE_URL as GOOGLE_BOOKS_API_URL};
+pub use open_library::{OpenLibrary, API_BASE_URL as OPEN_LIBRARY_API_URL};
+
+/// A source of book metadata that books can be searched for and added to the catalog from.
+#[async_trait]
+pub trait MetadataProvider: Send + Sync {
+    /// Searches the provider for a page of results. Qualifiers or filters that the provider
+    /// doesn't support are ignored. May return an error if the API request fails.
+    async fn search(&self, input: SearchInput) -> Result<SearchResultPage, BooksApiError>;
+
+    /// Fetches a book by the provider's ID for it, which is generally found via `search`. May
+    /// return an error if the API request fails or the book doesn't exist.
+    async fn get_by_id(&self, external_id: String) -> Result<SearchResult, BooksApiError>;
+}
+
+/// An error from a metadata provider's API. Surfaced to GraphQL clients with a `code` extension,
+/// e.g. `RATE_LIMITED`, so they can tell the cases apart.
+#[derive(Debug)]
+pub enum BooksApiError {
+    /// The provider has no book with the requested ID
+    NotFound { external_id: String },
+    /// The provider is rejecting requests until the quota resets
+    RateLimited { retry_after: Option<u64> },
+    /// The provider isn't set up, e.g. it needs an API key that wasn't configured
+    NotConfigured(String),
+    /// The request failed or the provider responded with an error
+    Upstream {
+        status: Option<u16>,
+        message: String,
+    },
+    /// The provider's response couldn't be understood
+    MalformedPayload(String),
+}
+
+impl Display for BooksApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound { external_id } => write!(f, "No book with ID {external_id} was found."),
+            Self::RateLimited { .. } => {
+                write!(f, "The book metadata provider is rate limiting requests.")
+            }
+            Self::NotConfigured(message) => write!(f, "{message}"),
+            Self::Upstream { message, .. } => {
+                write!(f, "The book metadata provider request failed: {message}")
+            }
+            Self::MalformedPayload(message) => write!(
+                f,
+                "The book metadata provider returned an unexpected response: {message}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BooksApiError {}
+
+impl ErrorExtensions for BooksApiError {
+    fn extend(&self) -> async_graphql::Error {
+        async_graphql::Error::new(self.to_string()).extend_with(|_, extensions| match self {
+            Self::NotFound { external_id } => {
+                extensions.set("code", "NOT_FOUND");
+                extensions.set("externalId", external_id.as_str());
+            }
+            Self::RateLimited { retry_after } => {
+                extensions.set("code", "RATE_LIMITED");
+                if let Some(seconds) = retry_after {
+                    extensions.set("retryAfter", *seconds);
+                }
+            }
+            Self::NotConfigured(_) => extensions.set("code", "NOT_CONFIGURED"),
+            Self::Upstream { status, .. } => {
+                extensions.set("code", "UPSTREAM_ERROR");
+                if let Some(status) = status {
+                    extensions.set("status", *status);
+                }
+            }
+            Self::MalformedPayload(_) => extensions.set("code", "MALFORMED_PAYLOAD"),
+        })
+    }
+}
+
+impl From<reqwest::Error> for BooksApiError {
+    fn from(error: reqwest::Error) -> Self {
+        Self::Upstream {
+            status: error.status().map(|status| status.as_u16()),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Sends a provider API request and deserializes its JSON response. Returns `None` for a 404 so
+/// that callers can report which book wasn't found.
+async fn fetch_json<T: DeserializeOwned>(
+    request: RequestBuilder,
+) -> Result<Option<T>, BooksApiError> {
+    let response = request.send().await?;
+    let status = response.status();
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok());
+    let body = response.text().await?;
+
+    if status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if let Some(error) = status_error(status, &body, retry_after) {
+        return Err(error);
+    }
+
+    parse_json(&body).map(Some)
+}
+
+/// Maps an unsuccessful response to an error, or returns `None` for a successful one.
+fn status_error(status: StatusCode, body: &str, retry_after: Option<u64>) -> Option<BooksApiError> {
+    // Google Books reports exceeded quotas as a 403 with a reason like `rateLimitExceeded` rather
+    // than a 429.
+    let quota_exceeded = status == StatusCode::FORBIDDEN
+        && [
+            "rateLimitExceeded",
+            "userRateLimitExceeded",
+            "dailyLimitExceeded",
+            "quotaExceeded",
+        ]
+        .iter()
+        .any(|reason| body.contains(reason));
+
+    if status == StatusCode::TOO_MANY_REQUESTS || quota_exceeded {
+        Some(BooksApiError::RateLimited { retry_after })
+    } else if !status.is_success() {
+        Some(BooksApiError::Upstream {
+            status: Some(status.as_u16()),
+            message: format!("responded with {status}"),
+        })
+    } else {
+        None
+    }
+}
+
+fn parse_json<T: DeserializeOwned>(body: &str) -> Result<T, BooksApiError> {
+    serde_json::from_str(body).map_err(|error| BooksApiError::MalformedPayload(error.to_string()))
+}
+
+/// The metadata providers available to the schema, one per `ExternalProvider`, behind a shared
+/// response cache.
+#[derive(Clone)]
+pub struct MetadataProviders {
+    google_books: Arc<dyn MetadataProvider>,
+    open_library: Arc<dyn MetadataProvider>,
+    cache: Arc<MetadataCache>,
+}
+
+impl MetadataProviders {
+    pub fn new(
+        google_books: Arc<dyn MetadataProvider>,
+        open_library: Arc<dyn MetadataProvider>,
+        cache: MetadataCache,
+    ) -> Self {
+        Self {
+            google_books,
+            open_library,
+            cache: Arc::new(cache),
+        }
+    }
+
+    /// Searches `provider`, using a cached page of results unless `bypass_cache` is set.
+    pub async fn search(
+        &self,
+        provider: ExternalProvider,
+        input: SearchInput,
+        bypass_cache: bool,
+    ) -> Result<SearchResultPage, BooksApiError> {
+        // The `Debug` output covers every field of the input, so equal searches share a key.
+        let key = format!("{provider:?}:search:{input:?}");
+        self.cache
+            .get_or_fetch(key, bypass_cache, self.get(provider).search(input))
+            .await
+    }
+
+    /// Fetches a book from `provider`, using a cached response unless `bypass_cache` is set. An
+    /// ISBN is looked up with `get_by_isbn`, since that's the ID books found by ISBN are given.
+    pub async fn get_by_id(
+        &self,
+        provider: ExternalProvider,
+        external_id: String,
+        bypass_cache: bool,
+    ) -> Result<SearchResult, BooksApiError> {
+        if let Ok(isbn) = external_id.parse::<Isbn>() {
+            return self.get_by_isbn(provider, isbn, bypass_cache).await;
+        }
+
+        let key = format!("{provider:?}:book:{external_id}");
+        self.cache
+            .get_or_fetch(key, bypass_cache, self.get(provider).get_by_id(external_id))
+            .await
+    }
+
+    /// Finds the edition of a book with `isbn` by searching `provider`, using a cached page of
+    /// results unless `bypass_cache` is set. The result is identified by its ISBN-13 rather than
+    /// the provider's ID, which Open Library shares between every edition of a work.
+    pub async fn get_by_isbn(
+        &self,
+        provider: ExternalProvider,
+        isbn: Isbn,
+        bypass_cache: bool,
+    ) -> Result<SearchResult, BooksApiError> {
+        let mut search = SearchInput::from_query(String::new());
+        search.isbn = Some(isbn.isbn_13.clone());
+        search.page_size = 1;
+        let mut result = self
+            .search(provider, search, bypass_cache)
+            .await?
+            .items
+            .into_iter()
+            .next()
+            .ok_or_else(|| BooksApiError::NotFound {
+                external_id: isbn.isbn_13.clone(),
+            })?;
+
+        // The provider may not list the ISBN that the book was found by.
+        result.id = isbn.isbn_13.clone();
+        result.isbn_10 = isbn.isbn_10;
+        result.isbn_13 = Some(isbn.isbn_13);
+
+        Ok(result)
+    }
+
+    pub fn cache_stats(&self) -> MetadataCacheStats {
+        self.cache.stats()
+    }
+
+    /// Returns the provider to use for `provider`.
+    fn get(&self, provider: ExternalProvider) -> &dyn MetadataProvider {
+        match provider {
+            ExternalProvider::GoogleBooks => self.google_books.as_ref(),
+            ExternalProvider::OpenLibrary => self.open_library.as_ref(),
+        }
+    }
+}
+
+/// Safely convert the first 4 characters of a published date from a YYYY-MM-DD String to a YYYY
+/// i32.
+pub fn parse_year(year: String) -> i32 {
+    year.chars()
+        .take(4)
+        .collect::<String>()
+        .parse::<i32>()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_error() {
+        assert!(status_error(StatusCode::OK, "{}", None).is_none());
+
+        assert!(matches!(
+            status_error(StatusCode::TOO_MANY_REQUESTS, "", Some(30)),
+            Some(BooksApiError::RateLimited {
+                retry_after: Some(30)
+            })
+        ));
+        assert!(matches!(
+            status_error(
+                StatusCode::FORBIDDEN,
+                include_str!("../../tests/fixtures/google_books/rate_limited.json"),
+                None
+            ),
+            Some(BooksApiError::RateLimited { retry_after: None })
+        ));
+        assert!(matches!(
+            status_error(StatusCode::FORBIDDEN, "{}", None),
+            Some(BooksApiError::Upstream {
+                status: Some(403),
+                ..
+            })
+        ));
+        assert!(matches!(
+            status_error(StatusCode::SERVICE_UNAVAILABLE, "", None),
+            Some(BooksApiError::Upstream {
+                status: Some(503),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_error_extensions() {
+        let error = BooksApiError::RateLimited {
+            retry_after: Some(30),
+        }
+        .extend();
+        let extensions = error.extensions.unwrap();
+
+        assert_eq!(
+            Some(&async_graphql::Value::from("RATE_LIMITED")),
+            extensions.get("code")
+        );
+        assert_eq!(
+            Some(&async_graphql::Value::from(30)),
+            extensions.get("retryAfter")
+        );
+    }
+
+    #[test]
+    fn test_parse_year() {
+        assert_eq!(1973, parse_year("1973-01-01".to_string()));
+        assert_eq!(1973, parse_year("1973-10-18T09:26:55−07:00".to_string()));
+        assert_eq!(123, parse_year("123".to_string()));
+        assert_eq!(0, parse_year("bad".to_string()));
+        assert_eq!(0, parse_year("".to_string()));
+    }
+}
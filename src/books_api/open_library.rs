@@ -0,0 +1,210 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+
+use super::{fetch_json, BooksApiError, MetadataProvider};
+use crate::graphql::{ExternalProvider, SearchInput, SearchOrder, SearchResult, SearchResultPage};
+
+/// The default URL of the search API, which `OpenLibrary::new` can be pointed elsewhere from.
+pub const API_BASE_URL: &str = "https://openlibrary.org/search.json";
+const COVERS_BASE_URL: &str = "https://covers.openlibrary.org/b/id";
+
+/// The fields requested from the search API, matching `SearchDoc`.
+const SEARCH_FIELDS: &str =
+    "key,title,author_name,number_of_pages_median,first_publish_year,cover_i,isbn";
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(rename = "numFound", default)]
+    num_found: i32,
+    docs: Vec<SearchDoc>,
+}
+
+#[derive(Deserialize)]
+struct SearchDoc {
+    /// The work's key, e.g. `/works/OL45804W`
+    key: String,
+    title: String,
+    #[serde(default)]
+    author_name: Vec<String>,
+    number_of_pages_median: Option<i32>,
+    first_publish_year: Option<i32>,
+    cover_i: Option<i64>,
+    /// The ISBNs of all of the work's editions, both 10 and 13 digit
+    #[serde(default)]
+    isbn: Vec<String>,
+}
+
+impl SearchDoc {
+    /// Serializes a `SearchDoc` to our `SearchResult` struct, identified by its work ID.
+    fn into_search_result(self) -> SearchResult {
+        SearchResult {
+            id: self.key.trim_start_matches("/works/").to_string(),
+            provider: ExternalProvider::OpenLibrary,
+            title: self.title,
+            authors: self.author_name,
+            pages: self.number_of_pages_median.unwrap_or_default(),
+            year: self.first_publish_year.unwrap_or_default(),
+            image_url: self
+                .cover_i
+                .map(|cover_id| format!("{COVERS_BASE_URL}/{cover_id}-M.jpg")),
+            isbn_10: self.isbn.iter().find(|isbn| isbn.len() == 10).cloned(),
+            isbn_13: self.isbn.iter().find(|isbn| isbn.len() == 13).cloned(),
+        }
+    }
+}
+
+/// Metadata provider backed by the Open Library search API. Books are identified by their Open
+/// Library work ID, e.g. `OL45804W`.
+pub struct OpenLibrary {
+    client: Client,
+    base_url: String,
+}
+
+impl OpenLibrary {
+    /// Creates a provider that sends requests to the search API at `base_url`, normally
+    /// `API_BASE_URL`.
+    pub fn new(base_url: String) -> Self {
+        Self {
+            client: Client::new(),
+            base_url,
+        }
+    }
+
+    async fn fetch(
+        &self,
+        mut params: Vec<(&str, String)>,
+    ) -> Result<SearchResponse, BooksApiError> {
+        params.push(("fields", SEARCH_FIELDS.to_string()));
+        let request = self.client.get(&self.base_url).query(&params);
+
+        let body = fetch_json::<SearchResponse>(request)
+            .await?
+            .ok_or_else(|| BooksApiError::Upstream {
+                status: Some(404),
+                message: "the search endpoint was not found".to_string(),
+            })?;
+
+        Ok(body)
+    }
+}
+
+/// Maps a search to the search API's parameters. Open Library has its own parameter for each of
+/// the qualifiers.
+fn search_params(input: &SearchInput) -> Vec<(&'static str, String)> {
+    let mut params = Vec::new();
+    if let Some(text) = input.text() {
+        params.push(("q", text.to_string()));
+    }
+    for (name, value) in input.qualifiers() {
+        let name = match name {
+            "intitle" => "title",
+            "inauthor" => "author",
+            "inpublisher" => "publisher",
+            name => name,
+        };
+        params.push((name, value.to_string()));
+    }
+    params.push(("offset", input.start_index.to_string()));
+    params.push(("limit", input.page_size.to_string()));
+    if let Some(language) = &input.lang_restrict {
+        params.push(("lang", language.clone()));
+    }
+    if input.order_by == SearchOrder::Newest {
+        params.push(("sort", "new".to_string()));
+    }
+
+    params
+}
+
+#[async_trait]
+impl MetadataProvider for OpenLibrary {
+    async fn search(&self, input: SearchInput) -> Result<SearchResultPage, BooksApiError> {
+        let body = self.fetch(search_params(&input)).await?;
+
+        Ok(SearchResultPage {
+            total_items: body.num_found,
+            start_index: input.start_index,
+            items: body
+                .docs
+                .into_iter()
+                .map(|doc| doc.into_search_result())
+                .collect(),
+        })
+    }
+
+    async fn get_by_id(&self, work_id: String) -> Result<SearchResult, BooksApiError> {
+        let body = self
+            .fetch(vec![
+                ("q", format!("key:/works/{work_id}")),
+                ("limit", "1".to_string()),
+            ])
+            .await?;
+
+        body.docs
+            .into_iter()
+            .next()
+            .map(|doc| doc.into_search_result())
+            .ok_or(BooksApiError::NotFound {
+                external_id: work_id,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_doc_into_search_result() {
+        let response: SearchResponse = serde_json::from_str(
+            r#"{"docs": [
+                {"key": "/works/OL45804W", "title": "Gravity's Rainbow", "author_name": ["Thomas Pynchon"], "number_of_pages_median": 776, "first_publish_year": 1973, "cover_i": 12345, "isbn": ["9780143039945", "0143039946"]},
+                {"key": "/works/OL1W", "title": "Untitled"}
+            ]}"#,
+        )
+        .unwrap();
+        let results: Vec<SearchResult> = response
+            .docs
+            .into_iter()
+            .map(|doc| doc.into_search_result())
+            .collect();
+
+        assert_eq!("OL45804W", results[0].id);
+        assert_eq!(vec!["Thomas Pynchon".to_string()], results[0].authors);
+        assert_eq!(776, results[0].pages);
+        assert_eq!(1973, results[0].year);
+        assert_eq!(
+            Some("https://covers.openlibrary.org/b/id/12345-M.jpg".to_string()),
+            results[0].image_url
+        );
+
+        assert_eq!(Some("0143039946".to_string()), results[0].isbn_10);
+        assert_eq!(Some("9780143039945".to_string()), results[0].isbn_13);
+
+        assert!(results[1].authors.is_empty());
+        assert_eq!(0, results[1].pages);
+        assert_eq!(None, results[1].image_url);
+    }
+
+    #[test]
+    fn test_search_params() {
+        let mut input = SearchInput::from_query("rainbow".to_string());
+        input.inauthor = Some("Pynchon".to_string());
+        input.start_index = 20;
+        input.lang_restrict = Some("en".to_string());
+        input.order_by = SearchOrder::Newest;
+
+        assert_eq!(
+            vec![
+                ("q", "rainbow".to_string()),
+                ("author", "Pynchon".to_string()),
+                ("offset", "20".to_string()),
+                ("limit", "10".to_string()),
+                ("lang", "en".to_string()),
+                ("sort", "new".to_string()),
+            ],
+            search_params(&input)
+        );
+    }
+}
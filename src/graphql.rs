@@ -2,26 +2,85 @@ use std::{fmt::Display, str::FromStr};
 
 use anyhow::{anyhow, Result};
 use async_graphql::{
-    http::GraphiQLSource, ComplexObject, Context, EmptySubscription, Enum, InputObject, Object,
-    Schema, SimpleObject,
+    connection::{self, Connection, Edge, OpaqueCursor},
+    dataloader::DataLoader,
+    http::GraphiQLSource,
+    ComplexObject, Context, EmptySubscription, Enum, ErrorExtensions, InputObject, MaybeUndefined,
+    Object, Schema, SimpleObject,
 };
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
 use axum::{extract::Extension, response::Html};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
-use crate::books_api::BooksApi;
-use crate::repository::Storage;
+use crate::books_api::{BooksApiError, MetadataProviders};
+use crate::isbn::Isbn;
+use crate::loader::{
+    AuthorCountsLoader, BookAuthorsLoader, BookSeriesLoader, BookTagsLoader, LibraryBookLoader,
+    NextInSeriesLoader, NotesLoader, ReadingProgressLoader, ReadingsLoader, StatusHistoryLoader,
+};
+use crate::repository::{
+    AuthorCounts, ExternalLink, KeysetPage, LibraryBookKey, PageArgs, SeriesEntry,
+    StatusChangeError, Storage,
+};
 
-#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq, sqlx::Type)]
-#[sqlx(rename_all = "lowercase", type_name = "status")]
+/// The page size used by external searches when none is given.
+const DEFAULT_SEARCH_PAGE_SIZE: i32 = 10;
+/// The largest page size an external search can be asked for. Google Books allows at most 40.
+const MAX_SEARCH_PAGE_SIZE: i32 = 40;
+/// The page size used by connections when neither `first` nor `last` is given.
+const DEFAULT_PAGE_SIZE: usize = 20;
+/// The largest page size a connection can be asked for.
+const MAX_PAGE_SIZE: usize = 100;
+
+/// A stable, opaque connection cursor wrapping a row ID.
+pub type IdCursor = OpaqueCursor<i32>;
+
+#[derive(Clone, Copy, Debug, Default, Enum, Eq, PartialEq, sqlx::Type)]
+#[sqlx(rename_all = "snake_case", type_name = "status")]
 pub enum ReadingStatus {
+    #[default]
     Unread,
     Reading,
     Read,
+    /// Started, then given up on
+    DidNotFinish,
+    /// Started, and set aside for now
+    Paused,
+    /// Not in the library yet
+    Wishlist,
 }
 
-impl Default for ReadingStatus {
-    fn default() -> Self {
-        Self::Unread
+impl ReadingStatus {
+    /// The statuses of books in the library, i.e. everything but the wishlist. Books are filtered
+    /// to these unless statuses are asked for.
+    pub const LIBRARY: [ReadingStatus; 5] = [
+        Self::Unread,
+        Self::Reading,
+        Self::Read,
+        Self::DidNotFinish,
+        Self::Paused,
+    ];
+
+    /// Whether a book with this status can be moved to `next`. Books come off the wishlist by
+    /// being bought or started, and only books that have been started can be paused or abandoned.
+    pub fn can_transition_to(self, next: ReadingStatus) -> bool {
+        self == next
+            || matches!(
+                (self, next),
+                (Self::Wishlist, Self::Unread | Self::Reading)
+                    | (Self::Unread, Self::Reading | Self::Read | Self::Wishlist)
+                    | (
+                        Self::Reading,
+                        Self::Unread | Self::Read | Self::DidNotFinish | Self::Paused
+                    )
+                    | (
+                        Self::Paused,
+                        Self::Reading | Self::Read | Self::DidNotFinish
+                    )
+                    | (Self::Read, Self::Unread | Self::Reading)
+                    | (Self::DidNotFinish, Self::Unread | Self::Reading)
+            )
     }
 }
 
@@ -31,6 +90,9 @@ impl Display for ReadingStatus {
             Self::Unread => write!(f, "unread"),
             Self::Read => write!(f, "read"),
             Self::Reading => write!(f, "reading"),
+            Self::DidNotFinish => write!(f, "did_not_finish"),
+            Self::Paused => write!(f, "paused"),
+            Self::Wishlist => write!(f, "wishlist"),
         }
     }
 }
@@ -43,6 +105,9 @@ impl FromStr for ReadingStatus {
             "unread" => Ok(Self::Unread),
             "reading" => Ok(Self::Reading),
             "read" => Ok(Self::Read),
+            "did_not_finish" => Ok(Self::DidNotFinish),
+            "paused" => Ok(Self::Paused),
+            "wishlist" => Ok(Self::Wishlist),
             _ => Err(anyhow!("Invalid reading status")),
         }
     }
@@ -55,6 +120,141 @@ impl sqlx::postgres::PgHasArrayType for ReadingStatus {
     }
 }
 
+/// The field to sort books by.
+#[derive(Clone, Copy, Debug, Default, Enum, Eq, PartialEq)]
+pub enum BookSort {
+    Title,
+    Author,
+    Year,
+    Pages,
+    /// The order books were added to the catalog in
+    #[default]
+    DateAdded,
+}
+
+#[derive(Clone, Copy, Debug, Default, Enum, Eq, PartialEq)]
+pub enum SortDirection {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// An external source of book metadata.
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Enum, Eq, Hash, PartialEq, Serialize, sqlx::Type,
+)]
+#[sqlx(rename_all = "snake_case", type_name = "provider")]
+pub enum ExternalProvider {
+    /// The Google Books API
+    #[default]
+    GoogleBooks,
+    /// The Open Library API
+    OpenLibrary,
+}
+
+impl sqlx::postgres::PgHasArrayType for ExternalProvider {
+    fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::with_name("_provider")
+    }
+}
+
+/// What a person is credited with on a book.
+#[derive(Clone, Copy, Debug, Default, Enum, Eq, Hash, PartialEq, sqlx::Type)]
+#[sqlx(rename_all = "snake_case", type_name = "author_role")]
+pub enum AuthorRole {
+    #[default]
+    Author,
+    Translator,
+    Editor,
+    Illustrator,
+}
+
+/// A person credited on one or more books.
+#[derive(Clone, Debug, Eq, PartialEq, SimpleObject, sqlx::FromRow)]
+#[graphql(complex)]
+pub struct Author {
+    pub id: i32,
+    pub name: String,
+}
+
+/// A person's credit on a book.
+#[derive(Clone, Debug, SimpleObject)]
+pub struct BookAuthor {
+    #[graphql(skip)]
+    pub book_id: i32,
+    pub author: Author,
+    pub role: AuthorRole,
+}
+
+#[derive(Clone, Debug, Eq, InputObject, PartialEq)]
+pub struct BookAuthorInput {
+    /// The person's name
+    pub name: String,
+    /// What the person is credited with. Defaults to AUTHOR.
+    #[graphql(default)]
+    pub role: AuthorRole,
+}
+
+impl BookAuthorInput {
+    pub fn author(name: String) -> Self {
+        Self {
+            name,
+            role: AuthorRole::Author,
+        }
+    }
+}
+
+/// A field of a book's details that can be refreshed from the provider it was added from.
+#[derive(Clone, Copy, Debug, Deserialize, Enum, Eq, Hash, PartialEq, Serialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case", type_name = "book_field")]
+pub enum BookField {
+    Title,
+    Author,
+    ImageUrl,
+    Year,
+    Pages,
+}
+
+impl BookField {
+    pub const ALL: [BookField; 5] = [
+        Self::Title,
+        Self::Author,
+        Self::ImageUrl,
+        Self::Year,
+        Self::Pages,
+    ];
+}
+
+impl sqlx::postgres::PgHasArrayType for BookField {
+    fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::with_name("_book_field")
+    }
+}
+
+#[derive(Clone, Debug, Default, InputObject)]
+pub struct BookFilter {
+    /// Only include books whose author contains this text, ignoring case
+    pub author: Option<String>,
+    /// Only include books whose title contains this text, ignoring case
+    pub title: Option<String>,
+    /// Only include books published in or after this year
+    pub min_year: Option<i32>,
+    /// Only include books published in or before this year
+    pub max_year: Option<i32>,
+    /// Only include books with at least this many pages
+    pub min_pages: Option<i32>,
+    /// Only include books with at most this many pages
+    pub max_pages: Option<i32>,
+    /// Only include books with one of these reading statuses. Defaults to every status but
+    /// WISHLIST.
+    pub statuses: Option<Vec<ReadingStatus>>,
+    /// Only include books with all of these tags
+    pub tags: Option<Vec<String>>,
+    /// Only include books on the shelf with this ID
+    pub shelf: Option<i32>,
+}
+
 #[derive(Clone, Debug, SimpleObject)]
 #[graphql(complex)]
 pub struct Book {
@@ -72,6 +272,72 @@ pub struct Book {
     pub pages: i32,
     /// The reading status of the book
     pub status: ReadingStatus,
+    /// The book's 10 digit ISBN
+    pub isbn_10: Option<String>,
+    /// The book's 13 digit ISBN
+    pub isbn_13: Option<String>,
+    /// The provider the book was added from, if it was added via `addExternalBook`
+    pub external_provider: Option<ExternalProvider>,
+    /// The provider's ID for the book
+    pub external_id: Option<String>,
+}
+
+/// A change to a book's reading status.
+#[derive(Clone, Debug, PartialEq, SimpleObject, sqlx::FromRow)]
+pub struct StatusEvent {
+    #[graphql(skip)]
+    pub book_id: i32,
+    /// The status the book changed to
+    pub status: ReadingStatus,
+    /// When the status changed
+    pub at: DateTime<Utc>,
+}
+
+/// How a reading ended.
+#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq, sqlx::Type)]
+#[sqlx(rename_all = "snake_case", type_name = "reading_outcome")]
+pub enum ReadingOutcome {
+    Finished,
+    DidNotFinish,
+}
+
+/// One read-through of a book. Readings start when the book is marked READING and end when it's
+/// marked READ or DID_NOT_FINISH.
+#[derive(Clone, Debug, PartialEq, SimpleObject, sqlx::FromRow)]
+#[graphql(complex)]
+pub struct Reading {
+    /// The reading ID
+    pub id: i32,
+    /// The ID of the book being read
+    pub book_id: i32,
+    /// When the reading started. Books marked READ without being started have no start.
+    pub started_at: Option<DateTime<Utc>>,
+    /// When the reading ended, whether the book was finished or not
+    pub finished_at: Option<DateTime<Utc>>,
+    /// How the reading ended. Readings in progress have no outcome.
+    pub outcome: Option<ReadingOutcome>,
+}
+
+/// How far a reading had got at a point in time.
+#[derive(Clone, Debug, PartialEq, SimpleObject, sqlx::FromRow)]
+pub struct ProgressEntry {
+    /// The progress entry ID
+    pub id: i32,
+    #[graphql(skip)]
+    pub reading_id: i32,
+    /// The page reached
+    pub page: i32,
+    /// When the page was reached
+    pub at: DateTime<Utc>,
+}
+
+#[derive(SimpleObject)]
+pub struct LogProgressPayload {
+    /// The book, marked READ if the last page was logged
+    pub book: Book,
+    /// The progress that was logged
+    pub progress: ProgressEntry,
+    pub success: bool,
 }
 
 #[derive(Clone, InputObject)]
@@ -97,6 +363,55 @@ pub struct AddGoogleBookInput {
     pub status: Option<ReadingStatus>,
 }
 
+#[derive(Clone, InputObject)]
+pub struct AddBookByIsbnInput {
+    /// The ISBN-10 or ISBN-13 of the book. Hyphens and spaces are ignored.
+    pub isbn: String,
+    /// The provider to look the book up with. Defaults to GOOGLE_BOOKS.
+    #[graphql(default)]
+    pub provider: ExternalProvider,
+    /// The reading status of the book. Defaults to UNREAD.
+    pub status: Option<ReadingStatus>,
+}
+
+#[derive(Clone, InputObject)]
+pub struct AddExternalBookInput {
+    /// The provider to fetch the book's details from.
+    pub provider: ExternalProvider,
+    /// The provider's ID for the book, as returned by `search`.
+    pub external_id: String,
+    /// An optional title of the book. This field will override the title taken from the provider.
+    pub title: Option<String>,
+    /// An optional author name. This field will override the author name taken from the provider.
+    pub author: Option<String>,
+    /// An optional link to an image of the book cover. This field will override the image URL
+    /// taken from the provider.
+    pub image_url: Option<String>,
+    /// An optional year that the book was published. This field will override the year taken from
+    /// the provider.
+    pub year: Option<i32>,
+    /// An optional number of pages in the book. This field will override the page count taken from
+    /// the provider.
+    pub pages: Option<i32>,
+    /// The reading status of the book. Defaults to UNREAD.
+    pub status: Option<ReadingStatus>,
+}
+
+impl From<AddGoogleBookInput> for AddExternalBookInput {
+    fn from(input: AddGoogleBookInput) -> Self {
+        Self {
+            provider: ExternalProvider::GoogleBooks,
+            external_id: input.google_books_id,
+            title: input.title,
+            author: input.author,
+            image_url: input.image_url,
+            year: input.year,
+            pages: input.pages,
+            status: input.status,
+        }
+    }
+}
+
 #[derive(Clone, InputObject)]
 pub struct AddBookInput {
     /// The title of the book
@@ -111,6 +426,22 @@ pub struct AddBookInput {
     pub pages: i32,
     /// The reading status of the book. Defaults to UNREAD.
     pub status: Option<ReadingStatus>,
+    /// The book's 10 digit ISBN
+    pub isbn_10: Option<String>,
+    /// The book's 13 digit ISBN
+    pub isbn_13: Option<String>,
+    /// Everyone credited on the book, in credit order. Defaults to just `author`, who must be the
+    /// primary author when this is given.
+    pub authors: Option<Vec<BookAuthorInput>>,
+    /// Set by `addExternalBook` to the provider the book came from
+    #[graphql(skip)]
+    pub external_provider: Option<ExternalProvider>,
+    /// Set by `addExternalBook` to the provider's ID for the book
+    #[graphql(skip)]
+    pub external_id: Option<String>,
+    /// Set by `addExternalBook` to the fields given in place of the provider's values
+    #[graphql(skip)]
+    pub overridden_fields: Vec<BookField>,
 }
 
 #[derive(SimpleObject)]
@@ -121,7 +452,151 @@ pub struct AddBookPayload {
     pub success: bool,
 }
 
+/// A field whose value differs between the library and the book's provider.
+#[derive(SimpleObject)]
+pub struct MetadataChange {
+    pub field: BookField,
+    /// The library's value before the refresh
+    pub old_value: Option<String>,
+    /// The provider's value
+    pub new_value: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct RefreshBookMetadataPayload {
+    /// The book after the refresh
+    pub book: Book,
+    /// The fields that were updated to the provider's values
+    pub changes: Vec<MetadataChange>,
+    /// The fields that differ from the provider's values but were kept, because they were
+    /// overridden when the book was added or edited since
+    pub skipped: Vec<MetadataChange>,
+    /// Why the book couldn't be refreshed. Only set by `refreshAllBookMetadata`, which carries on
+    /// with the other books; `refreshBookMetadata` returns an error instead.
+    pub error: Option<String>,
+}
+
+#[derive(Clone, InputObject)]
+pub struct UpdateBookInput {
+    /// The ID of the book to update
+    pub book_id: i32,
+    /// A new title for the book. Omit to keep the current title.
+    pub title: Option<String>,
+    /// A new author for the book. Omit to keep the current author.
+    pub author: Option<String>,
+    /// A new link to an image of the book cover. Omit to keep the current image, or set to null
+    /// to remove it.
+    pub image_url: MaybeUndefined<String>,
+    /// A new publication year for the book. Omit to keep the current year.
+    pub year: Option<i32>,
+    /// A new page count for the book. Omit to keep the current page count.
+    pub pages: Option<i32>,
+    /// Everyone credited on the book, in credit order, replacing the current credits. Omit to
+    /// keep the current credits, apart from the primary author if `author` is set. If both are
+    /// set, `author` must be the primary author here.
+    pub authors: Option<Vec<BookAuthorInput>>,
+    /// Set by `updateBook` to the fields it edits, which are kept when the book is refreshed
+    #[graphql(skip)]
+    pub overridden_fields: Vec<BookField>,
+}
+
+impl AddBookInput {
+    /// Everyone credited on the book, which is just `author` unless `authors` was given.
+    pub fn credits(&self) -> Vec<BookAuthorInput> {
+        self.authors
+            .clone()
+            .unwrap_or_else(|| vec![BookAuthorInput::author(self.author.clone())])
+    }
+}
+
+impl UpdateBookInput {
+    /// The fields that the update changes.
+    fn edited_fields(&self) -> Vec<BookField> {
+        [
+            (BookField::Title, self.title.is_some()),
+            (
+                BookField::Author,
+                self.author.is_some() || self.authors.is_some(),
+            ),
+            (BookField::ImageUrl, !self.image_url.is_undefined()),
+            (BookField::Year, self.year.is_some()),
+            (BookField::Pages, self.pages.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(field, edited)| edited.then_some(field))
+        .collect()
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct UpdateBookPayload {
+    /// The book that was updated
+    pub book: Book,
+    /// Did the operation succeed?
+    pub success: bool,
+}
+
+#[derive(SimpleObject)]
+pub struct DeleteBookPayload {
+    /// The ID of the book that was deleted
+    pub book_id: i32,
+    /// Did the operation succeed?
+    pub success: bool,
+}
+
+#[derive(SimpleObject)]
+pub struct MergeAuthorsPayload {
+    /// The author that the others were merged into
+    pub author: Author,
+    /// Did the operation succeed?
+    pub success: bool,
+}
+
+/// A named collection of books, kept in the order they were arranged in.
+#[derive(Clone, Debug, Eq, PartialEq, SimpleObject, sqlx::FromRow)]
+#[graphql(complex)]
+pub struct Shelf {
+    /// The shelf ID
+    pub id: i32,
+    /// The name of the shelf
+    pub name: String,
+}
+
+#[derive(SimpleObject)]
+pub struct ShelfPayload {
+    /// The shelf that was created or changed
+    pub shelf: Shelf,
+    /// Did the operation succeed?
+    pub success: bool,
+}
+
+/// A series of books, read in order.
+#[derive(Clone, Debug, PartialEq, SimpleObject, sqlx::FromRow)]
+#[graphql(complex)]
+pub struct Series {
+    /// The series ID
+    pub id: i32,
+    /// The name of the series
+    pub name: String,
+}
+
+#[derive(SimpleObject)]
+pub struct SeriesPayload {
+    /// The series that was created
+    pub series: Series,
+    /// Did the operation succeed?
+    pub success: bool,
+}
+
 #[derive(SimpleObject)]
+pub struct DeleteShelfPayload {
+    /// The ID of the shelf that was deleted
+    pub shelf_id: i32,
+    /// Did the operation succeed?
+    pub success: bool,
+}
+
+#[derive(Clone, Debug, SimpleObject)]
 pub struct Note {
     /// The note ID
     pub id: i32,
@@ -131,6 +606,8 @@ pub struct Note {
     pub note: String,
     /// An optional page number related to the number
     pub page: Option<i32>,
+    /// The ID of the reading the note was taken during, if any
+    pub reading_id: Option<i32>,
 }
 
 #[derive(InputObject)]
@@ -141,6 +618,8 @@ pub struct AddNoteInput {
     pub note: String,
     /// An optional page number related to the number
     pub page: Option<i32>,
+    /// An optional ID of one of the book's readings, to take the note during
+    pub reading_id: Option<i32>,
 }
 
 #[derive(SimpleObject)]
@@ -151,12 +630,45 @@ pub struct AddNotePayload {
     pub success: bool,
 }
 
+#[derive(InputObject)]
+pub struct UpdateNoteInput {
+    /// The ID of the note to update
+    pub note_id: i32,
+    /// New note content. Omit to keep the current content.
+    pub note: Option<String>,
+    /// A new page number related to the note. Omit to keep the current page, or set to null to
+    /// remove it.
+    pub page: MaybeUndefined<i32>,
+    /// The ID of one of the book's readings to take the note during. Omit to keep the current
+    /// reading, or set to null to detach the note from it.
+    pub reading_id: MaybeUndefined<i32>,
+}
+
+#[derive(SimpleObject)]
+pub struct UpdateNotePayload {
+    /// The note that was updated
+    pub note: Note,
+    /// Did the operation succeed?
+    pub success: bool,
+}
+
+#[derive(SimpleObject)]
+pub struct DeleteNotePayload {
+    /// The ID of the note that was deleted
+    pub note_id: i32,
+    /// Did the operation succeed?
+    pub success: bool,
+}
+
 #[derive(InputObject)]
 pub struct UpdateBookStatusInput {
     /// The ID of the book this note references
     pub book_id: i32,
     /// The reading status of the book
     pub status: ReadingStatus,
+    /// When the status changed. Defaults to now. Changes can be backdated, but not to before the
+    /// book's last change.
+    pub at: Option<DateTime<Utc>>,
 }
 
 #[derive(SimpleObject)]
@@ -167,10 +679,37 @@ pub struct UpdateBookStatusPayload {
     pub success: bool,
 }
 
-#[derive(SimpleObject)]
+/// The part of the library that matched a library search.
+#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq)]
+pub enum LibrarySearchMatch {
+    /// The book's title or author matched
+    Book,
+    /// The text of one of the book's notes matched
+    Note,
+}
+
+#[derive(Clone, Debug, SimpleObject)]
+pub struct LibrarySearchHit {
+    /// Whether the book itself or one of its notes matched
+    pub matched: LibrarySearchMatch,
+    /// How well the hit matches the query, higher being better. Only comparable between hits of
+    /// the same search.
+    pub rank: f64,
+    /// The matching text, with the matched terms wrapped in `<b>` tags
+    pub snippet: String,
+    /// The matching book, or the book the matching note belongs to
+    pub book: Book,
+    /// The matching note, if a note matched
+    pub note: Option<Note>,
+}
+
+#[derive(Deserialize, Serialize, SimpleObject)]
+#[graphql(complex)]
 pub struct SearchResult {
-    /// The ID of the search result item
+    /// The ID of the search result item, unique within its provider
     pub id: String,
+    /// The provider the search result came from
+    pub provider: ExternalProvider,
     /// The title of the book
     pub title: String,
     /// The authors of the book
@@ -181,6 +720,130 @@ pub struct SearchResult {
     pub year: i32,
     /// An image URL of the book cover
     pub image_url: Option<String>,
+    /// The book's 10 digit ISBN
+    pub isbn_10: Option<String>,
+    /// The book's 13 digit ISBN
+    pub isbn_13: Option<String>,
+}
+
+impl SearchResult {
+    /// The key used to find this result's book in the library.
+    fn library_key(&self) -> LibraryBookKey {
+        LibraryBookKey {
+            provider: self.provider,
+            external_id: self.id.clone(),
+            isbn_10: self.isbn_10.clone(),
+            isbn_13: self.isbn_13.clone(),
+            title: self.title.clone(),
+            author: self.authors.first().cloned(),
+        }
+    }
+}
+
+/// The order of external search results.
+#[derive(Clone, Copy, Debug, Default, Enum, Eq, PartialEq)]
+pub enum SearchOrder {
+    /// The best matches first
+    #[default]
+    Relevance,
+    /// The most recently published books first
+    Newest,
+}
+
+#[derive(Clone, Debug, InputObject)]
+pub struct SearchInput {
+    /// Free text to search for
+    pub query: Option<String>,
+    /// Only match books with this text in the title
+    pub intitle: Option<String>,
+    /// Only match books with this text in an author's name
+    pub inauthor: Option<String>,
+    /// Only match books with this ISBN
+    pub isbn: Option<String>,
+    /// Only match books with this subject
+    pub subject: Option<String>,
+    /// Only match books with this text in the publisher's name
+    pub inpublisher: Option<String>,
+    /// The offset of the first result to return. Defaults to 0.
+    #[graphql(default)]
+    pub start_index: i32,
+    /// The number of results to return, between 1 and 40. Defaults to 10.
+    #[graphql(default = 10)]
+    pub page_size: i32,
+    /// Only match books in this language, as an ISO 639-1 code such as `en`
+    pub lang_restrict: Option<String>,
+    /// The order of the results. Defaults to RELEVANCE.
+    #[graphql(default)]
+    pub order_by: SearchOrder,
+}
+
+impl SearchInput {
+    /// A free text search for the first page of results.
+    pub fn from_query(query: String) -> Self {
+        Self {
+            query: Some(query),
+            intitle: None,
+            inauthor: None,
+            isbn: None,
+            subject: None,
+            inpublisher: None,
+            start_index: 0,
+            page_size: DEFAULT_SEARCH_PAGE_SIZE,
+            lang_restrict: None,
+            order_by: SearchOrder::default(),
+        }
+    }
+
+    /// The qualifiers that were given, paired with their Google Books query syntax names.
+    pub fn qualifiers(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("intitle", &self.intitle),
+            ("inauthor", &self.inauthor),
+            ("isbn", &self.isbn),
+            ("subject", &self.subject),
+            ("inpublisher", &self.inpublisher),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(|value| (name, value))
+        })
+        .collect()
+    }
+
+    /// The free text to search for, if any was given.
+    pub fn text(&self) -> Option<&str> {
+        self.query
+            .as_deref()
+            .map(str::trim)
+            .filter(|query| !query.is_empty())
+    }
+}
+
+#[derive(Deserialize, Serialize, SimpleObject)]
+pub struct SearchResultPage {
+    /// The total number of books matching the search, as estimated by the provider
+    pub total_items: i32,
+    /// The offset of the first result in this page
+    pub start_index: i32,
+    /// The matching books in this page
+    pub items: Vec<SearchResult>,
+}
+
+/// How well the metadata provider response cache is working since the server started.
+#[derive(SimpleObject)]
+pub struct MetadataCacheStats {
+    /// Requests answered from the in-process cache
+    pub hits: u64,
+    /// Requests answered from the persistent cache table
+    pub persistent_hits: u64,
+    /// Requests sent to a provider, including those that bypassed the cache
+    pub misses: u64,
+    /// The number of responses in the in-process cache
+    pub entries: usize,
 }
 
 pub struct Query;
@@ -203,49 +866,206 @@ impl std::error::Error for GraphQLError {}
 
 #[Object]
 impl Query {
-    /// Fetch a book by either its ID or by its title
+    /// Fetch a book by its ID, its title or its ISBN
     async fn book(
         &self,
         ctx: &Context<'_>,
         book_id: Option<i32>,
         title: Option<String>,
+        isbn: Option<String>,
     ) -> Result<Option<Book>> {
         let repository = ctx.data_unchecked::<Storage>().clone();
 
         if let Some(input) = book_id {
-            return repository.lock().await.get_book_by_id(input).await;
+            return repository.get_book_by_id(input).await;
         } else if let Some(input) = title {
-            return repository.lock().await.get_book_by_title(input).await;
+            return repository.get_book_by_title(input).await;
+        } else if let Some(input) = isbn {
+            let isbn: Isbn = input
+                .parse()
+                .map_err(|error| GraphQLError::BadInput(format!("{error}")))?;
+            return repository.get_book_by_isbn(isbn.isbn_13).await;
         }
         Err(GraphQLError::BadInput(
-            "Either `bookId` or `title` input value required for query.".to_string(),
+            "Either `bookId`, `title` or `isbn` input value required for query.".to_string(),
         )
         .into())
     }
 
-    /// Fetch all books with an optional status specifier
+    /// Fetch all books with an optional status specifier, filter and sort order. Wishlisted books
+    /// are left out unless WISHLIST is asked for.
     async fn books(
         &self,
         ctx: &Context<'_>,
         status: Option<ReadingStatus>,
+        filter: Option<BookFilter>,
+        #[graphql(default)] sort: BookSort,
+        #[graphql(default)] direction: SortDirection,
     ) -> Result<Option<Vec<Book>>> {
         let repository = ctx.data_unchecked::<Storage>().clone();
 
-        let books = repository.lock().await.get_books(status).await?;
+        // `status` narrows down any statuses given in the filter.
+        let mut filter = filter.unwrap_or_default();
+        if let Some(status) = status {
+            filter.statuses = match filter.statuses {
+                Some(statuses) => Some(statuses.into_iter().filter(|s| *s == status).collect()),
+                None => Some(vec![status]),
+            };
+        }
+
+        let books = repository.get_books(filter, sort, direction).await?;
         Ok(books)
     }
 
-    /// Search for a book to add to the catalog via Google Books API
-    async fn search(&self, query: String) -> Result<Vec<SearchResult>> {
-        let results = BooksApi::search(query).await?;
-        Ok(results)
+    /// Fetch a page of books ordered by ID, with an optional status specifier. Wishlisted books are
+    /// left out unless WISHLIST is asked for.
+    async fn books_connection(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<ReadingStatus>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> async_graphql::Result<Connection<IdCursor, Book>> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        connection::query(
+            after,
+            before,
+            first,
+            last,
+            |after, before, first, last| async move {
+                let page = page_args(after, before, first, last)?;
+                let books = repository.get_books_page(status, page).await?;
+                Ok::<_, async_graphql::Error>(into_connection(books, |book| book.id))
+            },
+        )
+        .await
+    }
+
+    /// Search the titles, authors and notes of the books in the catalog, best matches first
+    async fn library_search(
+        &self,
+        ctx: &Context<'_>,
+        query: String,
+        #[graphql(default = 20)] limit: i32,
+    ) -> Result<Vec<LibrarySearchHit>> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        if query.trim().is_empty() {
+            return Err(GraphQLError::BadInput("Search query cannot be empty.".to_string()).into());
+        }
+        if limit < 1 || limit as usize > MAX_PAGE_SIZE {
+            return Err(GraphQLError::BadInput(format!(
+                "Search limit must be between 1 and {MAX_PAGE_SIZE}."
+            ))
+            .into());
+        }
+
+        let hits = repository.search_library(query, limit as usize).await?;
+        Ok(hits)
+    }
+
+    /// Search for a book to add to the catalog via an external provider. Defaults to Google Books
+    /// API. Results are cached unless `bypassCache` is set.
+    async fn search(
+        &self,
+        ctx: &Context<'_>,
+        query: String,
+        #[graphql(default)] provider: ExternalProvider,
+        #[graphql(default)] bypass_cache: bool,
+    ) -> async_graphql::Result<Vec<SearchResult>> {
+        let page = self
+            .search_books(ctx, SearchInput::from_query(query), provider, bypass_cache)
+            .await?;
+        Ok(page.items)
+    }
+
+    /// Search for books to add to the catalog via an external provider, with field qualifiers,
+    /// paging and filters. Defaults to Google Books API. Results are cached unless `bypassCache`
+    /// is set.
+    async fn search_books(
+        &self,
+        ctx: &Context<'_>,
+        input: SearchInput,
+        #[graphql(default)] provider: ExternalProvider,
+        #[graphql(default)] bypass_cache: bool,
+    ) -> async_graphql::Result<SearchResultPage> {
+        let providers = ctx.data_unchecked::<MetadataProviders>();
+
+        if input.text().is_none() && input.qualifiers().is_empty() {
+            return Err(GraphQLError::BadInput(
+                "Search needs a query or at least one qualifier.".to_string(),
+            )
+            .into());
+        }
+        if input.start_index < 0 {
+            return Err(GraphQLError::BadInput(
+                "Search start index cannot be less than 0.".to_string(),
+            )
+            .into());
+        }
+        if !(1..=MAX_SEARCH_PAGE_SIZE).contains(&input.page_size) {
+            return Err(GraphQLError::BadInput(format!(
+                "Search page size must be between 1 and {MAX_SEARCH_PAGE_SIZE}."
+            ))
+            .into());
+        }
+
+        let page = providers
+            .search(provider, input, bypass_cache)
+            .await
+            .map_err(|error| error.extend())?;
+        Ok(page)
+    }
+
+    /// Fetch every author, ordered by name
+    async fn authors(&self, ctx: &Context<'_>) -> Result<Vec<Author>> {
+        let repository = ctx.data_unchecked::<Storage>();
+        repository.get_authors().await
+    }
+
+    /// Fetch an author by their ID
+    async fn author(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Author>> {
+        let repository = ctx.data_unchecked::<Storage>();
+        repository.get_author_by_id(id).await
+    }
+
+    /// Fetch every tag that's on a book, in alphabetical order
+    async fn tags(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        let repository = ctx.data_unchecked::<Storage>();
+        repository.get_tags().await
+    }
+
+    /// Fetch every shelf, ordered by name
+    async fn shelves(&self, ctx: &Context<'_>) -> Result<Vec<Shelf>> {
+        let repository = ctx.data_unchecked::<Storage>();
+        repository.get_shelves().await
+    }
+
+    /// Fetch a shelf by its ID
+    async fn shelf(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Shelf>> {
+        let repository = ctx.data_unchecked::<Storage>();
+        repository.get_shelf_by_id(id).await
+    }
+
+    /// Fetch a series by its ID
+    async fn series(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Series>> {
+        let repository = ctx.data_unchecked::<Storage>();
+        repository.get_series_by_id(id).await
+    }
+
+    /// Hit and miss counts for the cache of external provider responses
+    async fn metadata_cache_stats(&self, ctx: &Context<'_>) -> MetadataCacheStats {
+        ctx.data_unchecked::<MetadataProviders>().cache_stats()
     }
 }
 
 #[Object]
 impl Mutation {
     /// Add a book by manually entering in the book details.
-    async fn add_book(&self, ctx: &Context<'_>, input: AddBookInput) -> Result<AddBookPayload> {
+    async fn add_book(&self, ctx: &Context<'_>, mut input: AddBookInput) -> Result<AddBookPayload> {
         let repository = ctx.data_unchecked::<Storage>().clone();
 
         if input.pages < 1 {
@@ -255,7 +1075,17 @@ impl Mutation {
             .into());
         }
 
-        let book = repository.lock().await.add_book(input).await?;
+        // Both ISBN forms are stored, whichever of them was given.
+        if let Some(isbn) = validate_isbns(input.isbn_10.as_deref(), input.isbn_13.as_deref())? {
+            input.isbn_10 = isbn.isbn_10;
+            input.isbn_13 = Some(isbn.isbn_13);
+        }
+        if let Some(credits) = &input.authors {
+            validate_credits(credits)?;
+            validate_primary_author(&input.author, credits)?;
+        }
+
+        let book = repository.add_book(input).await?;
 
         Ok(AddBookPayload {
             book,
@@ -264,12 +1094,25 @@ impl Mutation {
     }
 
     /// Add a book via Google Books API search.
+    #[graphql(deprecation = "Use `addExternalBook` instead.")]
     async fn add_google_book(
         &self,
         ctx: &Context<'_>,
         input: AddGoogleBookInput,
-    ) -> Result<AddBookPayload> {
+    ) -> async_graphql::Result<AddBookPayload> {
+        self.add_external_book(ctx, input.into(), false).await
+    }
+
+    /// Add a book found via an external provider's search. The book's details are looked up with
+    /// the provider, using a cached response unless `bypassCache` is set.
+    async fn add_external_book(
+        &self,
+        ctx: &Context<'_>,
+        input: AddExternalBookInput,
+        #[graphql(default)] bypass_cache: bool,
+    ) -> async_graphql::Result<AddBookPayload> {
         let repository = ctx.data_unchecked::<Storage>().clone();
+        let providers = ctx.data_unchecked::<MetadataProviders>();
 
         if let Some(page_count) = input.pages {
             if page_count < 1 {
@@ -280,33 +1123,56 @@ impl Mutation {
             }
         }
 
-        let book_result = BooksApi::get_by_id(input.google_books_id).await.unwrap();
+        let book_result = providers
+            .get_by_id(input.provider, input.external_id.clone(), bypass_cache)
+            .await
+            .map_err(|error| error.extend())?;
 
-        let author = book_result
-            .authors
-            .first()
-            .expect("Author not found in search result.")
-            .to_owned();
+        let book_input = external_book_input(book_result, input.into())?;
+        let book = repository.add_book(book_input).await?;
 
-        let mut image_url = String::new();
-        if let Some(links) = book_result.image_links {
-            image_url = links.thumbnail;
-        }
+        Ok(AddBookPayload {
+            book,
+            success: true,
+        })
+    }
 
-        let year = BooksApi::parse_year(book_result.published_date);
+    /// Add a book by its ISBN, looking up the rest of its details with an external provider. The
+    /// lookup uses a cached response unless `bypassCache` is set.
+    async fn add_book_by_isbn(
+        &self,
+        ctx: &Context<'_>,
+        input: AddBookByIsbnInput,
+        #[graphql(default)] bypass_cache: bool,
+    ) -> async_graphql::Result<AddBookPayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+        let providers = ctx.data_unchecked::<MetadataProviders>();
 
-        // Use optional overrides if any were provided; otherwise, default to results from Google
-        // Books API.
-        let book_input = AddBookInput {
-            title: input.title.unwrap_or(book_result.title),
-            author: input.author.unwrap_or(author),
-            image_url: Some(image_url),
-            year: input.year.unwrap_or(year),
-            pages: input.pages.unwrap_or(book_result.page_count),
-            status: input.status,
-        };
+        let isbn: Isbn = input
+            .isbn
+            .parse()
+            .map_err(|error| GraphQLError::BadInput(format!("{error}")))?;
+
+        let book_result = providers
+            .get_by_isbn(input.provider, isbn, bypass_cache)
+            .await
+            .map_err(|error| match error {
+                BooksApiError::NotFound { .. } => async_graphql::Error::new(format!(
+                    "No book with ISBN {} was found.",
+                    input.isbn
+                ))
+                .extend_with(|_, extensions| extensions.set("code", "NOT_FOUND")),
+                error => error.extend(),
+            })?;
 
-        let book = repository.lock().await.add_book(book_input).await?;
+        let book_input = external_book_input(
+            book_result,
+            BookOverrides {
+                status: input.status,
+                ..BookOverrides::default()
+            },
+        )?;
+        let book = repository.add_book(book_input).await?;
 
         Ok(AddBookPayload {
             book,
@@ -314,19 +1180,448 @@ impl Mutation {
         })
     }
 
-    /// Update a book's reading status
+    /// Update any of a book's details. Fields that are omitted from the input are left unchanged.
+    async fn update_book(
+        &self,
+        ctx: &Context<'_>,
+        mut input: UpdateBookInput,
+    ) -> Result<UpdateBookPayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        if let Some(page_count) = input.pages {
+            if page_count < 1 {
+                return Err(GraphQLError::BadInput(
+                    "Book page number cannot be less than 1.".to_string(),
+                )
+                .into());
+            }
+        }
+
+        // `author` is the book's primary credit, so it's kept in step with `authors`. Setting just
+        // `author` replaces the primary credit in the repository.
+        if let Some(credits) = &input.authors {
+            validate_credits(credits)?;
+            match &input.author {
+                Some(author) => validate_primary_author(author, credits)?,
+                None => input.author = primary_author(credits),
+            }
+        }
+
+        // Edited fields are kept when the book's metadata is refreshed from its provider.
+        input.overridden_fields = input.edited_fields();
+        let book = repository.update_book(input).await?;
+
+        Ok(UpdateBookPayload {
+            book,
+            success: true,
+        })
+    }
+
+    /// Update a book's details from the provider it was added from, returning the fields that
+    /// changed. Fields the provider has no value for are kept. Fields that were overridden when
+    /// the book was added, or edited since, are only updated when they're listed in `fields`.
+    /// Defaults to all fields.
+    async fn refresh_book_metadata(
+        &self,
+        ctx: &Context<'_>,
+        book_id: i32,
+        fields: Option<Vec<BookField>>,
+    ) -> async_graphql::Result<RefreshBookMetadataPayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+        let providers = ctx.data_unchecked::<MetadataProviders>();
+
+        let book = repository
+            .get_book_by_id(book_id)
+            .await?
+            .ok_or_else(|| GraphQLError::BadInput("Book with that ID not found".to_string()))?;
+        let link = repository
+            .get_external_links(Some(book_id))
+            .await?
+            .pop()
+            .ok_or_else(|| {
+                GraphQLError::BadInput(
+                    "This book wasn't added from a provider, so it can't be refreshed.".to_string(),
+                )
+            })?;
+
+        refresh_book(&repository, providers, book, link, fields.as_deref()).await
+    }
+
+    /// Refresh every book that was added from a provider, like `refreshBookMetadata`. Books that
+    /// can't be refreshed have their `error` set, and the rest are still refreshed.
+    async fn refresh_all_book_metadata(
+        &self,
+        ctx: &Context<'_>,
+        fields: Option<Vec<BookField>>,
+    ) -> Result<Vec<RefreshBookMetadataPayload>> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+        let providers = ctx.data_unchecked::<MetadataProviders>();
+
+        let mut payloads = Vec::new();
+        for link in repository.get_external_links(None).await? {
+            let Some(book) = repository.get_book_by_id(link.book_id).await? else {
+                continue;
+            };
+            let payload = match refresh_book(
+                &repository,
+                providers,
+                book.clone(),
+                link,
+                fields.as_deref(),
+            )
+            .await
+            {
+                Ok(payload) => payload,
+                Err(error) => RefreshBookMetadataPayload {
+                    book,
+                    changes: Vec::new(),
+                    skipped: Vec::new(),
+                    error: Some(error.message),
+                },
+            };
+            payloads.push(payload);
+        }
+
+        Ok(payloads)
+    }
+
+    /// Tag a book. Tags the book already has are ignored.
+    async fn add_book_tags(
+        &self,
+        ctx: &Context<'_>,
+        book_id: i32,
+        tags: Vec<String>,
+    ) -> Result<UpdateBookPayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        let tags = validate_tags(tags)?;
+        repository.add_book_tags(book_id, &tags).await?;
+        let book = repository
+            .get_book_by_id(book_id)
+            .await?
+            .ok_or_else(|| anyhow!("No book with ID {} found.", book_id))?;
+
+        Ok(UpdateBookPayload {
+            book,
+            success: true,
+        })
+    }
+
+    /// Remove tags from a book. Tags the book doesn't have are ignored.
+    async fn remove_book_tags(
+        &self,
+        ctx: &Context<'_>,
+        book_id: i32,
+        tags: Vec<String>,
+    ) -> Result<UpdateBookPayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        let tags = validate_tags(tags)?;
+        let book = repository
+            .get_book_by_id(book_id)
+            .await?
+            .ok_or_else(|| anyhow!("No book with ID {} found.", book_id))?;
+        repository.remove_book_tags(book_id, &tags).await?;
+
+        Ok(UpdateBookPayload {
+            book,
+            success: true,
+        })
+    }
+
+    /// Create an empty shelf
+    async fn create_shelf(&self, ctx: &Context<'_>, name: String) -> Result<ShelfPayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        let shelf = repository
+            .create_shelf(&validate_shelf_name(&name)?)
+            .await?;
+
+        Ok(ShelfPayload {
+            shelf,
+            success: true,
+        })
+    }
+
+    /// Rename a shelf
+    async fn rename_shelf(
+        &self,
+        ctx: &Context<'_>,
+        shelf_id: i32,
+        name: String,
+    ) -> Result<ShelfPayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        let shelf = repository
+            .rename_shelf(shelf_id, &validate_shelf_name(&name)?)
+            .await?;
+
+        Ok(ShelfPayload {
+            shelf,
+            success: true,
+        })
+    }
+
+    /// Delete a shelf. The books on it stay in the library.
+    async fn delete_shelf(&self, ctx: &Context<'_>, shelf_id: i32) -> Result<DeleteShelfPayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        repository.delete_shelf(shelf_id).await?;
+
+        Ok(DeleteShelfPayload {
+            shelf_id,
+            success: true,
+        })
+    }
+
+    /// Put a book on a shelf at a zero-based position, or at the end if no position is given. A
+    /// book that's already on the shelf is moved to the position.
+    async fn add_book_to_shelf(
+        &self,
+        ctx: &Context<'_>,
+        shelf_id: i32,
+        book_id: i32,
+        position: Option<i32>,
+    ) -> Result<ShelfPayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        let position = position
+            .map(|position| {
+                usize::try_from(position).map_err(|_| {
+                    GraphQLError::BadInput("Shelf position cannot be negative.".to_string())
+                })
+            })
+            .transpose()?;
+        repository
+            .add_book_to_shelf(shelf_id, book_id, position)
+            .await?;
+        let shelf = repository
+            .get_shelf_by_id(shelf_id)
+            .await?
+            .ok_or_else(|| anyhow!("No shelf with ID {} found.", shelf_id))?;
+
+        Ok(ShelfPayload {
+            shelf,
+            success: true,
+        })
+    }
+
+    /// Take a book off a shelf. The book stays in the library.
+    async fn remove_book_from_shelf(
+        &self,
+        ctx: &Context<'_>,
+        shelf_id: i32,
+        book_id: i32,
+    ) -> Result<ShelfPayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        repository.remove_book_from_shelf(shelf_id, book_id).await?;
+        let shelf = repository
+            .get_shelf_by_id(shelf_id)
+            .await?
+            .ok_or_else(|| anyhow!("No shelf with ID {} found.", shelf_id))?;
+
+        Ok(ShelfPayload {
+            shelf,
+            success: true,
+        })
+    }
+
+    /// Create an empty series
+    async fn create_series(&self, ctx: &Context<'_>, name: String) -> Result<SeriesPayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(GraphQLError::BadInput("Series name cannot be empty.".to_string()).into());
+        }
+        let series = repository.create_series(name).await?;
+
+        Ok(SeriesPayload {
+            series,
+            success: true,
+        })
+    }
+
+    /// Put a book in a series at a position in reading order, taking it out of any series it was
+    /// in. Positions can be fractional, such as 2.5 for a novella between the second and third
+    /// books.
+    async fn set_book_series(
+        &self,
+        ctx: &Context<'_>,
+        book_id: i32,
+        series_id: i32,
+        position: f64,
+    ) -> Result<UpdateBookPayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        if position < 0.0 {
+            return Err(
+                GraphQLError::BadInput("Series position cannot be negative.".to_string()).into(),
+            );
+        }
+        repository
+            .set_book_series(book_id, series_id, position)
+            .await?;
+        let book = repository
+            .get_book_by_id(book_id)
+            .await?
+            .ok_or_else(|| anyhow!("No book with ID {} found.", book_id))?;
+
+        Ok(UpdateBookPayload {
+            book,
+            success: true,
+        })
+    }
+
+    /// Take a book out of its series
+    async fn remove_book_from_series(
+        &self,
+        ctx: &Context<'_>,
+        book_id: i32,
+    ) -> Result<UpdateBookPayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        repository.remove_book_from_series(book_id).await?;
+        let book = repository
+            .get_book_by_id(book_id)
+            .await?
+            .ok_or_else(|| anyhow!("No book with ID {} found.", book_id))?;
+
+        Ok(UpdateBookPayload {
+            book,
+            success: true,
+        })
+    }
+
+    /// Delete a book. Any notes for the book are deleted along with it.
+    async fn delete_book(&self, ctx: &Context<'_>, book_id: i32) -> Result<DeleteBookPayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        repository.delete_book(book_id).await?;
+
+        Ok(DeleteBookPayload {
+            book_id,
+            success: true,
+        })
+    }
+
+    /// Merge authors who are the same person, such as "J. L. Borges" into "Jorge Luis Borges".
+    /// Every credit of the source authors moves to the target author, books whose author is a
+    /// source's name are renamed to the target's, and the source authors are deleted. Nothing
+    /// changes if any part of the merge fails.
+    async fn merge_authors(
+        &self,
+        ctx: &Context<'_>,
+        mut source_ids: Vec<i32>,
+        target_id: i32,
+    ) -> Result<MergeAuthorsPayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        source_ids.sort_unstable();
+        source_ids.dedup();
+        if source_ids.is_empty() {
+            return Err(GraphQLError::BadInput(
+                "At least one source author is required.".to_string(),
+            )
+            .into());
+        }
+        if source_ids.contains(&target_id) {
+            return Err(GraphQLError::BadInput(
+                "An author can't be merged into themselves.".to_string(),
+            )
+            .into());
+        }
+
+        let author = repository.merge_authors(&source_ids, target_id).await?;
+
+        Ok(MergeAuthorsPayload {
+            author,
+            success: true,
+        })
+    }
+
+    /// Update a book's reading status. Only some changes are allowed: books come off the wishlist
+    /// as UNREAD or READING, and only books that have been started can be PAUSED or marked
+    /// DID_NOT_FINISH.
     async fn update_book_status(
         &self,
         ctx: &Context<'_>,
         input: UpdateBookStatusInput,
     ) -> Result<UpdateBookStatusPayload> {
         let repository = ctx.data_unchecked::<Storage>().clone();
+        let at = input.at.unwrap_or_else(Utc::now);
+
+        validate_status_time(at)?;
 
         let book = repository
-            .lock()
+            .update_book_status(input.book_id, input.status, at)
             .await
-            .update_book_status(input.book_id, input.status)
-            .await?;
+            .map_err(status_change_error)?;
+        Ok(UpdateBookStatusPayload {
+            book,
+            success: true,
+        })
+    }
+
+    /// Log how far through a book its current reading has got, as either a page or a percentage
+    /// of the book. Logging the last page marks the book READ. `at` defaults to now.
+    async fn log_progress(
+        &self,
+        ctx: &Context<'_>,
+        book_id: i32,
+        page: Option<i32>,
+        percent: Option<f64>,
+        at: Option<DateTime<Utc>>,
+    ) -> Result<LogProgressPayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+        let at = at.unwrap_or_else(Utc::now);
+
+        let book = repository
+            .get_book_by_id(book_id)
+            .await?
+            .ok_or_else(|| GraphQLError::BadInput("Book with that ID not found".to_string()))?;
+        let page = match (page, percent) {
+            (Some(page), None) => page,
+            (None, Some(percent)) => percent_to_page(&book, percent)?,
+            _ => {
+                return Err(GraphQLError::BadInput(
+                    "Either `page` or `percent` input value required, but not both.".to_string(),
+                )
+                .into())
+            }
+        };
+        validate_page(&book, page, "Progress")?;
+        validate_progress_time(at)?;
+
+        let (book, progress) = repository
+            .log_progress(book.id, page, at)
+            .await
+            .map_err(status_change_error)?;
+        Ok(LogProgressPayload {
+            book,
+            progress,
+            success: true,
+        })
+    }
+
+    /// Start reading a book that's been read again, as a new reading. The earlier readings and
+    /// their dates are kept. `at` defaults to now.
+    async fn start_rereading(
+        &self,
+        ctx: &Context<'_>,
+        book_id: i32,
+        at: Option<DateTime<Utc>>,
+    ) -> Result<UpdateBookStatusPayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+        let at = at.unwrap_or_else(Utc::now);
+
+        validate_status_time(at)?;
+
+        let book = repository
+            .start_rereading(book_id, at)
+            .await
+            .map_err(status_change_error)?;
         Ok(UpdateBookStatusPayload {
             book,
             success: true,
@@ -338,11 +1633,7 @@ impl Mutation {
         let repository = ctx.data_unchecked::<Storage>().clone();
 
         // Fetch requested book for validation
-        let book = repository
-            .lock()
-            .await
-            .get_book_by_id(input.book_id)
-            .await?;
+        let book = repository.get_book_by_id(input.book_id).await?;
 
         if book.is_none() {
             return Err(GraphQLError::BadInput("Book with that ID not found".to_string()).into());
@@ -350,15 +1641,13 @@ impl Mutation {
         let book = book.unwrap();
 
         if let Some(note_page) = input.page {
-            if note_page > book.pages {
-                return Err(GraphQLError::BadInput(
-                    "Note page number cannot be greater than the highest page count of the book."
-                        .to_string(),
-                )
-                .into());
-            } else if note_page < 1 {
+            validate_page(&book, note_page, "Note")?;
+        }
+        if let Some(reading_id) = input.reading_id {
+            let reading = repository.get_reading_by_id(reading_id).await?;
+            if reading.is_none_or(|reading| reading.book_id != book.id) {
                 return Err(GraphQLError::BadInput(
-                    "Note page number cannot be less than 1.".to_string(),
+                    "Reading with that ID not found for this book".to_string(),
                 )
                 .into());
             }
@@ -368,23 +1657,736 @@ impl Mutation {
             book_id: input.book_id,
             note: input.note,
             page: input.page,
+            reading_id: input.reading_id,
         };
-        let note = repository.lock().await.add_note(note_input).await?;
+        let note = repository.add_note(note_input).await?;
         Ok(AddNotePayload {
             note,
             success: true,
         })
     }
+
+    /// Update the content, page or reading of an existing note. Fields that are omitted from the input are
+    /// left unchanged.
+    async fn update_note(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateNoteInput,
+    ) -> Result<UpdateNotePayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        // Fetch the note and its book for validation
+        let note = repository.get_note_by_id(input.note_id).await?;
+
+        if note.is_none() {
+            return Err(GraphQLError::BadInput("Note with that ID not found".to_string()).into());
+        };
+        let note = note.unwrap();
+
+        if let Some(note_page) = input.page.value() {
+            let book = repository.get_book_by_id(note.book_id).await?;
+
+            if book.is_none() {
+                return Err(
+                    GraphQLError::BadInput("Book with that ID not found".to_string()).into(),
+                );
+            };
+            validate_page(&book.unwrap(), *note_page, "Note")?;
+        }
+        if let Some(reading_id) = input.reading_id.value() {
+            let reading = repository.get_reading_by_id(*reading_id).await?;
+            if reading.is_none_or(|reading| reading.book_id != note.book_id) {
+                return Err(GraphQLError::BadInput(
+                    "Reading with that ID not found for this book".to_string(),
+                )
+                .into());
+            }
+        }
+
+        let note = repository.update_note(input).await?;
+        Ok(UpdateNotePayload {
+            note,
+            success: true,
+        })
+    }
+
+    /// Delete a note
+    async fn delete_note(&self, ctx: &Context<'_>, note_id: i32) -> Result<DeleteNotePayload> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        if repository.get_note_by_id(note_id).await?.is_none() {
+            return Err(GraphQLError::BadInput("Note with that ID not found".to_string()).into());
+        }
+
+        repository.delete_note(note_id).await?;
+
+        Ok(DeleteNotePayload {
+            note_id,
+            success: true,
+        })
+    }
+}
+
+/// Converts connection arguments to keyset pagination arguments.
+fn page_args(
+    after: Option<IdCursor>,
+    before: Option<IdCursor>,
+    first: Option<usize>,
+    last: Option<usize>,
+) -> Result<PageArgs, GraphQLError> {
+    let limit = first.or(last).unwrap_or(DEFAULT_PAGE_SIZE);
+    if limit > MAX_PAGE_SIZE {
+        return Err(GraphQLError::BadInput(format!(
+            "Page size cannot be greater than {MAX_PAGE_SIZE}."
+        )));
+    }
+
+    Ok(PageArgs {
+        after: after.map(|cursor| cursor.0),
+        before: before.map(|cursor| cursor.0),
+        limit,
+        from_end: last.is_some(),
+    })
+}
+
+/// Builds a connection from a page of rows, using each row's ID as its cursor.
+fn into_connection<T: async_graphql::OutputType>(
+    page: KeysetPage<T>,
+    id: impl Fn(&T) -> i32,
+) -> Connection<IdCursor, T> {
+    let mut connection = Connection::new(page.has_previous_page, page.has_next_page);
+    connection.edges.extend(
+        page.items
+            .into_iter()
+            .map(|item| Edge::new(OpaqueCursor(id(&item)), item)),
+    );
+    connection
+}
+
+/// The details given to use in place of a provider's when adding a book from it.
+#[derive(Default)]
+struct BookOverrides {
+    title: Option<String>,
+    author: Option<String>,
+    image_url: Option<String>,
+    year: Option<i32>,
+    pages: Option<i32>,
+    status: Option<ReadingStatus>,
+}
+
+impl From<AddExternalBookInput> for BookOverrides {
+    fn from(input: AddExternalBookInput) -> Self {
+        Self {
+            title: input.title,
+            author: input.author,
+            image_url: input.image_url,
+            year: input.year,
+            pages: input.pages,
+            status: input.status,
+        }
+    }
+}
+
+/// Builds the input for adding an external search result, using the overrides in `input` where
+/// they were given.
+fn external_book_input(
+    book_result: SearchResult,
+    input: BookOverrides,
+) -> Result<AddBookInput, GraphQLError> {
+    let overridden_fields = [
+        (BookField::Title, input.title.is_some()),
+        (BookField::Author, input.author.is_some()),
+        (BookField::ImageUrl, input.image_url.is_some()),
+        (BookField::Year, input.year.is_some()),
+        (BookField::Pages, input.pages.is_some()),
+    ]
+    .into_iter()
+    .filter_map(|(field, overridden)| overridden.then_some(field))
+    .collect();
+
+    let authors = match input.author {
+        Some(author) => vec![BookAuthorInput::author(author)],
+        None => provider_credits(&book_result.authors),
+    };
+    let author = authors
+        .first()
+        .map(|credit| credit.name.clone())
+        .ok_or_else(|| {
+            GraphQLError::BadInput(
+                "Author not found in search result. Provide an author to add this book."
+                    .to_string(),
+            )
+        })?;
+
+    // Providers' ISBNs are stored in both forms when they're valid, and dropped when they aren't.
+    let isbn = book_result
+        .isbn_13
+        .as_deref()
+        .or(book_result.isbn_10.as_deref())
+        .and_then(|isbn| isbn.parse::<Isbn>().ok());
+
+    Ok(AddBookInput {
+        title: input.title.unwrap_or(book_result.title),
+        author,
+        image_url: input.image_url.or(book_result.image_url),
+        year: input.year.unwrap_or(book_result.year),
+        pages: input.pages.unwrap_or(book_result.pages),
+        status: input.status,
+        isbn_10: isbn.as_ref().and_then(|isbn| isbn.isbn_10.clone()),
+        isbn_13: isbn.map(|isbn| isbn.isbn_13),
+        external_provider: Some(book_result.provider),
+        external_id: Some(book_result.id),
+        authors: Some(authors),
+        overridden_fields,
+    })
+}
+
+/// Credits a provider's list of authors in the AUTHOR role, skipping any repeated names.
+fn provider_credits(authors: &[String]) -> Vec<BookAuthorInput> {
+    let mut credits: Vec<BookAuthorInput> = Vec::new();
+    for name in authors {
+        if !credits.iter().any(|credit| credit.name == *name) {
+            credits.push(BookAuthorInput::author(name.clone()));
+        }
+    }
+    credits
+}
+
+/// The name to use as a book's `author`: its first AUTHOR credit, or else its first credit.
+fn primary_author(credits: &[BookAuthorInput]) -> Option<String> {
+    credits
+        .iter()
+        .find(|credit| credit.role == AuthorRole::Author)
+        .or(credits.first())
+        .map(|credit| credit.name.clone())
+}
+
+/// Diffs a book against its provider's current details and updates the fields that differ.
+/// Overridden fields are skipped unless they're in `fields`, in which case they're updated and
+/// stop being overridden.
+async fn refresh_book(
+    repository: &Storage,
+    providers: &MetadataProviders,
+    book: Book,
+    link: ExternalLink,
+    fields: Option<&[BookField]>,
+) -> async_graphql::Result<RefreshBookMetadataPayload> {
+    // A refresh is for picking up the provider's latest details, so it skips the cache.
+    let result = providers
+        .get_by_id(link.provider, link.external_id, true)
+        .await
+        .map_err(|error| error.extend())?;
+    let credits = repository.get_book_authors(&[book.id]).await?;
+    // Providers only list authors, so the AUTHOR credits are compared and any others are kept.
+    let joined_authors = |names: Vec<&String>| {
+        (!names.is_empty()).then(|| {
+            names
+                .into_iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+    };
+    let provider_authors = provider_credits(&result.authors);
+
+    let mut update = UpdateBookInput {
+        book_id: book.id,
+        title: None,
+        author: None,
+        image_url: MaybeUndefined::Undefined,
+        year: None,
+        pages: None,
+        authors: None,
+        overridden_fields: Vec::new(),
+    };
+    let mut changes = Vec::new();
+    let mut skipped = Vec::new();
+    let mut overridden_fields = link.overridden_fields.clone();
+
+    for field in BookField::ALL {
+        if fields.is_some_and(|fields| !fields.contains(&field)) {
+            continue;
+        }
+        // Providers use 0 for years and page counts they don't know.
+        let (old_value, new_value) = match field {
+            BookField::Title => (Some(book.title.clone()), Some(result.title.clone())),
+            BookField::Author => (
+                joined_authors(
+                    credits
+                        .iter()
+                        .filter(|credit| credit.role == AuthorRole::Author)
+                        .map(|credit| &credit.author.name)
+                        .collect(),
+                ),
+                joined_authors(provider_authors.iter().map(|credit| &credit.name).collect()),
+            ),
+            BookField::ImageUrl => (book.image_url.clone(), result.image_url.clone()),
+            BookField::Year => (
+                Some(book.year.to_string()),
+                (result.year != 0).then(|| result.year.to_string()),
+            ),
+            BookField::Pages => (
+                Some(book.pages.to_string()),
+                (result.pages > 0).then(|| result.pages.to_string()),
+            ),
+        };
+        // Values the provider doesn't have are kept rather than cleared.
+        if new_value.is_none() {
+            continue;
+        }
+        if fields.is_some() {
+            overridden_fields.retain(|overridden| *overridden != field);
+        }
+        if new_value == old_value {
+            continue;
+        }
+
+        let change = MetadataChange {
+            field,
+            old_value,
+            new_value,
+        };
+        if overridden_fields.contains(&field) {
+            skipped.push(change);
+            continue;
+        }
+        match field {
+            BookField::Title => update.title = Some(result.title.clone()),
+            BookField::Author => {
+                let other_credits = credits
+                    .iter()
+                    .filter(|credit| credit.role != AuthorRole::Author)
+                    .map(|credit| BookAuthorInput {
+                        name: credit.author.name.clone(),
+                        role: credit.role,
+                    });
+                let authors: Vec<_> = provider_authors
+                    .iter()
+                    .cloned()
+                    .chain(other_credits)
+                    .collect();
+                update.author = primary_author(&authors);
+                update.authors = Some(authors);
+            }
+            BookField::ImageUrl => {
+                update.image_url = MaybeUndefined::from(Some(result.image_url.clone()))
+            }
+            BookField::Year => update.year = Some(result.year),
+            BookField::Pages => update.pages = Some(result.pages),
+        }
+        changes.push(change);
+    }
+
+    let book = if changes.is_empty() {
+        book
+    } else {
+        repository.update_book(update).await?
+    };
+    if overridden_fields != link.overridden_fields {
+        repository
+            .set_overridden_fields(book.id, &overridden_fields)
+            .await?;
+    }
+
+    Ok(RefreshBookMetadataPayload {
+        book,
+        changes,
+        skipped,
+        error: None,
+    })
+}
+
+/// Trims tags and drops repeated ones, returning an error if there are none or any are blank.
+fn validate_tags(tags: Vec<String>) -> Result<Vec<String>, GraphQLError> {
+    let mut validated: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return Err(GraphQLError::BadInput("Tags cannot be empty.".to_string()));
+        }
+        if !validated.iter().any(|validated| validated == tag) {
+            validated.push(tag.to_string());
+        }
+    }
+    if validated.is_empty() {
+        return Err(GraphQLError::BadInput(
+            "At least one tag is required.".to_string(),
+        ));
+    }
+    Ok(validated)
+}
+
+/// Trims a shelf name, returning an error if it's blank.
+fn validate_shelf_name(name: &str) -> Result<String, GraphQLError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(GraphQLError::BadInput(
+            "Shelf name cannot be empty.".to_string(),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Validates a book's credits, which need at least one person and can't credit anyone twice in the
+/// same role.
+fn validate_credits(credits: &[BookAuthorInput]) -> Result<(), GraphQLError> {
+    if credits.is_empty() {
+        return Err(GraphQLError::BadInput(
+            "A book needs at least one author.".to_string(),
+        ));
+    }
+    if credits.iter().any(|credit| credit.name.trim().is_empty()) {
+        return Err(GraphQLError::BadInput(
+            "Author name cannot be empty.".to_string(),
+        ));
+    }
+    for (index, credit) in credits.iter().enumerate() {
+        if credits[..index].contains(credit) {
+            return Err(GraphQLError::BadInput(
+                "An author can only be credited once per role.".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that a book's `author` is the primary credit in its `authors`.
+fn validate_primary_author(author: &str, credits: &[BookAuthorInput]) -> Result<(), GraphQLError> {
+    if primary_author(credits).as_deref() != Some(author) {
+        return Err(GraphQLError::BadInput(
+            "Author must be the primary author in authors.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Validates the ISBNs given for a book, which must be the same book if both are given.
+fn validate_isbns(
+    isbn_10: Option<&str>,
+    isbn_13: Option<&str>,
+) -> Result<Option<Isbn>, GraphQLError> {
+    let parse = |isbn: Option<&str>| {
+        isbn.map(|isbn| isbn.parse::<Isbn>())
+            .transpose()
+            .map_err(|error| GraphQLError::BadInput(format!("{error}")))
+    };
+
+    match (parse(isbn_10)?, parse(isbn_13)?) {
+        (Some(isbn_10), Some(isbn_13)) if isbn_10 != isbn_13 => Err(GraphQLError::BadInput(
+            "The ISBN-10 and ISBN-13 are for different books.".to_string(),
+        )),
+        (isbn_10, isbn_13) => Ok(isbn_13.or(isbn_10)),
+    }
+}
+
+/// Maps a status change that the book's state doesn't allow to bad input, passing any other
+/// error through.
+fn status_change_error(error: anyhow::Error) -> anyhow::Error {
+    match error.downcast::<StatusChangeError>() {
+        Ok(error) => GraphQLError::BadInput(error.to_string()).into(),
+        Err(error) => error,
+    }
+}
+
+/// Validates that a status change at `at` isn't in the future. `update_book_status` checks that
+/// it isn't earlier than the book's last change.
+fn validate_status_time(at: DateTime<Utc>) -> Result<(), GraphQLError> {
+    if at > Utc::now() {
+        return Err(GraphQLError::BadInput(
+            "A status change can't be in the future.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Validates that progress logged at `at` isn't in the future. `log_progress` checks that it
+/// isn't earlier than the book's last status change.
+fn validate_progress_time(at: DateTime<Utc>) -> Result<(), GraphQLError> {
+    if at > Utc::now() {
+        return Err(GraphQLError::BadInput(
+            "Progress can't be logged in the future.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Works out the page `percent` of the way through a book, rounding up so that any progress
+/// reaches at least the first page.
+fn percent_to_page(book: &Book, percent: f64) -> Result<i32, GraphQLError> {
+    if !(percent > 0.0 && percent <= 100.0) {
+        return Err(GraphQLError::BadInput(
+            "Progress percent must be greater than 0 and at most 100.".to_string(),
+        ));
+    }
+    Ok((percent / 100.0 * f64::from(book.pages)).ceil() as i32)
+}
+
+/// Validates that a page number falls within the book, i.e. between 1 and `book.pages`. `subject`
+/// names what the page number is for in the error, e.g. "Note".
+fn validate_page(book: &Book, page: i32, subject: &str) -> Result<(), GraphQLError> {
+    if page > book.pages {
+        return Err(GraphQLError::BadInput(format!(
+            "{subject} page number cannot be greater than the highest page count of the book."
+        )));
+    } else if page < 1 {
+        return Err(GraphQLError::BadInput(format!(
+            "{subject} page number cannot be less than 1."
+        )));
+    }
+    Ok(())
 }
 
 #[ComplexObject]
 impl Book {
     /// All notes related to the given book
     async fn notes(&self, ctx: &Context<'_>) -> Result<Option<Vec<Note>>> {
-        let repository = ctx.data_unchecked::<Storage>().clone();
-        let notes = repository.lock().await.get_notes_by_book(self.id).await?;
+        let loader = ctx.data_unchecked::<DataLoader<NotesLoader>>();
+        let notes = loader
+            .load_one(self.id)
+            .await
+            .map_err(|error| anyhow!(error))?;
         Ok(notes)
     }
+
+    /// A page of notes related to the given book, ordered by ID
+    async fn notes_connection(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> async_graphql::Result<Connection<IdCursor, Note>> {
+        let repository = ctx.data_unchecked::<Storage>().clone();
+
+        connection::query(
+            after,
+            before,
+            first,
+            last,
+            |after, before, first, last| async move {
+                let page = page_args(after, before, first, last)?;
+                let notes = repository.get_notes_page(self.id, page).await?;
+                Ok::<_, async_graphql::Error>(into_connection(notes, |note| note.id))
+            },
+        )
+        .await
+    }
+
+    /// The book's tags, in alphabetical order
+    async fn tags(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        let loader = ctx.data_unchecked::<DataLoader<BookTagsLoader>>();
+        let tags = loader
+            .load_one(self.id)
+            .await
+            .map_err(|error| anyhow!(error))?;
+        Ok(tags.unwrap_or_default())
+    }
+
+    /// The series the book belongs to, if any
+    async fn series(&self, ctx: &Context<'_>) -> Result<Option<Series>> {
+        Ok(self.series_entry(ctx).await?.map(|entry| entry.series))
+    }
+
+    /// The book's position in its series' reading order
+    async fn series_position(&self, ctx: &Context<'_>) -> Result<Option<f64>> {
+        Ok(self.series_entry(ctx).await?.map(|entry| entry.position))
+    }
+
+    /// The first unread book after this one in its series
+    async fn next_in_series(&self, ctx: &Context<'_>) -> Result<Option<Book>> {
+        let loader = ctx.data_unchecked::<DataLoader<NextInSeriesLoader>>();
+        let next = loader
+            .load_one(self.id)
+            .await
+            .map_err(|error| anyhow!(error))?;
+        Ok(next)
+    }
+
+    /// Everyone credited on the book, in credit order
+    async fn authors(&self, ctx: &Context<'_>) -> Result<Vec<BookAuthor>> {
+        let loader = ctx.data_unchecked::<DataLoader<BookAuthorsLoader>>();
+        let credits = loader
+            .load_one(self.id)
+            .await
+            .map_err(|error| anyhow!(error))?;
+        Ok(credits.unwrap_or_default())
+    }
+
+    /// Every change to the book's reading status, oldest first
+    async fn status_history(&self, ctx: &Context<'_>) -> Result<Vec<StatusEvent>> {
+        let loader = ctx.data_unchecked::<DataLoader<StatusHistoryLoader>>();
+        let events = loader
+            .load_one(self.id)
+            .await
+            .map_err(|error| anyhow!(error))?;
+        Ok(events.unwrap_or_default())
+    }
+
+    /// Every read-through of the book, oldest first
+    async fn readings(&self, ctx: &Context<'_>) -> Result<Vec<Reading>> {
+        self.all_readings(ctx).await
+    }
+
+    /// When the book's latest reading started. Resuming a paused book doesn't count as starting
+    /// it.
+    async fn started_at(&self, ctx: &Context<'_>) -> Result<Option<DateTime<Utc>>> {
+        let readings = self.all_readings(ctx).await?;
+        Ok(readings.last().and_then(|reading| reading.started_at))
+    }
+
+    /// The page the book's latest reading has got to. Books whose latest reading was finished are
+    /// on their last page.
+    async fn current_page(&self, ctx: &Context<'_>) -> Result<Option<i32>> {
+        self.latest_page(ctx).await
+    }
+
+    /// How far through the book its latest reading has got, as a percentage
+    async fn percent_complete(&self, ctx: &Context<'_>) -> Result<Option<f64>> {
+        let page = self.latest_page(ctx).await?;
+        Ok(page.map(|page| f64::from(page) / f64::from(self.pages) * 100.0))
+    }
+
+    /// When the book's latest reading was finished, unless it's in progress or was given up on
+    async fn finished_at(&self, ctx: &Context<'_>) -> Result<Option<DateTime<Utc>>> {
+        let readings = self.all_readings(ctx).await?;
+        Ok(readings
+            .last()
+            .filter(|reading| reading.outcome == Some(ReadingOutcome::Finished))
+            .and_then(|reading| reading.finished_at))
+    }
+}
+
+impl Book {
+    async fn series_entry(&self, ctx: &Context<'_>) -> Result<Option<SeriesEntry>> {
+        let loader = ctx.data_unchecked::<DataLoader<BookSeriesLoader>>();
+        let entry = loader
+            .load_one(self.id)
+            .await
+            .map_err(|error| anyhow!(error))?;
+        Ok(entry)
+    }
+
+    async fn all_readings(&self, ctx: &Context<'_>) -> Result<Vec<Reading>> {
+        let loader = ctx.data_unchecked::<DataLoader<ReadingsLoader>>();
+        let readings = loader
+            .load_one(self.id)
+            .await
+            .map_err(|error| anyhow!(error))?;
+        Ok(readings.unwrap_or_default())
+    }
+
+    async fn latest_page(&self, ctx: &Context<'_>) -> Result<Option<i32>> {
+        let Some(reading) = self.all_readings(ctx).await?.pop() else {
+            return Ok(None);
+        };
+        if reading.outcome == Some(ReadingOutcome::Finished) {
+            return Ok(Some(self.pages));
+        }
+        let progress = reading.progress_entries(ctx).await?;
+        Ok(progress.last().map(|entry| entry.page))
+    }
+}
+
+#[ComplexObject]
+impl Reading {
+    /// The progress logged during this reading, oldest first
+    async fn progress(&self, ctx: &Context<'_>) -> Result<Vec<ProgressEntry>> {
+        self.progress_entries(ctx).await
+    }
+
+    /// The notes taken during this reading
+    async fn notes(&self, ctx: &Context<'_>) -> Result<Vec<Note>> {
+        let loader = ctx.data_unchecked::<DataLoader<NotesLoader>>();
+        let notes = loader
+            .load_one(self.book_id)
+            .await
+            .map_err(|error| anyhow!(error))?;
+        Ok(notes
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|note| note.reading_id == Some(self.id))
+            .collect())
+    }
+}
+
+impl Reading {
+    async fn progress_entries(&self, ctx: &Context<'_>) -> Result<Vec<ProgressEntry>> {
+        let loader = ctx.data_unchecked::<DataLoader<ReadingProgressLoader>>();
+        let progress = loader
+            .load_one(self.id)
+            .await
+            .map_err(|error| anyhow!(error))?;
+        Ok(progress.unwrap_or_default())
+    }
+}
+
+#[ComplexObject]
+impl Author {
+    /// The books crediting this person in any role, ordered by ID
+    async fn books(&self, ctx: &Context<'_>) -> Result<Vec<Book>> {
+        let repository = ctx.data_unchecked::<Storage>();
+        repository.get_books_by_author(self.id).await
+    }
+
+    /// The number of books crediting this person in any role
+    async fn book_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        Ok(self.counts(ctx).await?.books)
+    }
+
+    /// The number of books crediting this person that have been read
+    async fn read_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        Ok(self.counts(ctx).await?.read)
+    }
+}
+
+impl Author {
+    async fn counts(&self, ctx: &Context<'_>) -> Result<AuthorCounts> {
+        let loader = ctx.data_unchecked::<DataLoader<AuthorCountsLoader>>();
+        let counts = loader
+            .load_one(self.id)
+            .await
+            .map_err(|error| anyhow!(error))?;
+        // Authors without any books are left out of the counts.
+        Ok(counts.unwrap_or_default())
+    }
+}
+
+#[ComplexObject]
+impl Series {
+    /// The books in the series, in reading order
+    async fn books(&self, ctx: &Context<'_>) -> Result<Vec<Book>> {
+        let repository = ctx.data_unchecked::<Storage>();
+        repository.get_series_books(self.id).await
+    }
+}
+
+#[ComplexObject]
+impl Shelf {
+    /// The books on the shelf, in shelf order
+    async fn books(&self, ctx: &Context<'_>) -> Result<Vec<Book>> {
+        let repository = ctx.data_unchecked::<Storage>();
+        repository.get_shelf_books(self.id).await
+    }
+}
+
+#[ComplexObject]
+impl SearchResult {
+    /// Is this book already in the library? Matched by external ID, then ISBN, then title and
+    /// author.
+    async fn in_library(&self, ctx: &Context<'_>) -> Result<bool> {
+        Ok(self.library_book(ctx).await?.is_some())
+    }
+
+    /// The library's copy of this book, if it has been added
+    async fn library_book(&self, ctx: &Context<'_>) -> Result<Option<Book>> {
+        let loader = ctx.data_unchecked::<DataLoader<LibraryBookLoader>>();
+        let book = loader
+            .load_one(self.library_key())
+            .await
+            .map_err(|error| anyhow!(error))?;
+        Ok(book)
+    }
 }
 
 pub async fn graphql_handler(
@@ -0,0 +1,167 @@
+use std::{fmt::Display, str::FromStr};
+
+/// A validated ISBN in both of its forms. Every ISBN-10 has an ISBN-13 equivalent, but only
+/// ISBN-13s with the `978` prefix have an ISBN-10 equivalent.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Isbn {
+    pub isbn_10: Option<String>,
+    pub isbn_13: String,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum IsbnError {
+    /// Not 10 or 13 digits, ignoring hyphens and spaces
+    InvalidFormat(String),
+    /// The check digit doesn't match the other digits
+    InvalidChecksum(String),
+}
+
+impl Display for IsbnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidFormat(isbn) => write!(f, "`{isbn}` is not a 10 or 13 digit ISBN."),
+            Self::InvalidChecksum(isbn) => {
+                write!(f, "`{isbn}` is not a valid ISBN: its check digit is wrong.")
+            }
+        }
+    }
+}
+
+impl std::error::Error for IsbnError {}
+
+impl FromStr for Isbn {
+    type Err = IsbnError;
+
+    /// Parses an ISBN-10 or ISBN-13, ignoring hyphens and spaces, and validates its check digit.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let isbn: String = s
+            .chars()
+            .filter(|c| *c != '-' && !c.is_whitespace())
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+
+        // Checked up front so that the byte slicing below can't split a character.
+        if !isbn.is_ascii() {
+            return Err(IsbnError::InvalidFormat(s.to_string()));
+        }
+
+        match isbn.len() {
+            10 if is_isbn_10(&isbn) => {
+                if isbn_10_check_digit(&isbn[..9]) != isbn.chars().last() {
+                    return Err(IsbnError::InvalidChecksum(s.to_string()));
+                }
+                let isbn_13 = format!("978{}", &isbn[..9]);
+                Ok(Self {
+                    isbn_13: format!("{isbn_13}{}", isbn_13_check_digit(&isbn_13)),
+                    isbn_10: Some(isbn),
+                })
+            }
+            13 if isbn.chars().all(|c| c.is_ascii_digit()) => {
+                if isbn_13_check_digit(&isbn[..12]) != isbn.chars().last().unwrap() {
+                    return Err(IsbnError::InvalidChecksum(s.to_string()));
+                }
+                let isbn_10 = isbn.strip_prefix("978").and_then(|rest| {
+                    Some(format!(
+                        "{}{}",
+                        &rest[..9],
+                        isbn_10_check_digit(&rest[..9])?
+                    ))
+                });
+                Ok(Self {
+                    isbn_10,
+                    isbn_13: isbn,
+                })
+            }
+            _ => Err(IsbnError::InvalidFormat(s.to_string())),
+        }
+    }
+}
+
+/// Nine digits followed by a digit or `X`.
+fn is_isbn_10(isbn: &str) -> bool {
+    isbn[..9].chars().all(|c| c.is_ascii_digit())
+        && isbn[9..].chars().all(|c| c.is_ascii_digit() || c == 'X')
+}
+
+/// The check digit for the first 9 digits of an ISBN-10, where 10 is written as `X`.
+fn isbn_10_check_digit(digits: &str) -> Option<char> {
+    let sum: u32 = digits
+        .chars()
+        .zip((2..=10).rev())
+        .map(|(digit, weight)| digit.to_digit(10).map(|digit| digit * weight))
+        .sum::<Option<u32>>()?;
+
+    match (11 - sum % 11) % 11 {
+        10 => Some('X'),
+        check => char::from_digit(check, 10),
+    }
+}
+
+/// The check digit for the first 12 digits of an ISBN-13.
+fn isbn_13_check_digit(digits: &str) -> char {
+    let sum: u32 = digits
+        .chars()
+        .filter_map(|digit| digit.to_digit(10))
+        .zip([1, 3].into_iter().cycle())
+        .map(|(digit, weight)| digit * weight)
+        .sum();
+
+    char::from_digit((10 - sum % 10) % 10, 10).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_isbn_10() {
+        let isbn: Isbn = "0-14-303994-6".parse().unwrap();
+        assert_eq!(Some("0143039946".to_string()), isbn.isbn_10);
+        assert_eq!("9780143039945", isbn.isbn_13);
+
+        // A check digit of 10 is written as X.
+        let isbn: Isbn = "080442957x".parse().unwrap();
+        assert_eq!(Some("080442957X".to_string()), isbn.isbn_10);
+        assert_eq!("9780804429573", isbn.isbn_13);
+    }
+
+    #[test]
+    fn test_parse_isbn_13() {
+        let isbn: Isbn = "978 0 553 80457 7".parse().unwrap();
+        assert_eq!(Some("055380457X".to_string()), isbn.isbn_10);
+        assert_eq!("9780553804577", isbn.isbn_13);
+
+        // ISBN-13s outside the 978 prefix have no ISBN-10 equivalent.
+        let isbn: Isbn = "979-10-90636-07-1".parse().unwrap();
+        assert_eq!(None, isbn.isbn_10);
+        assert_eq!("9791090636071", isbn.isbn_13);
+    }
+
+    #[test]
+    fn test_invalid_isbns() {
+        assert_eq!(
+            Err(IsbnError::InvalidChecksum("0143039947".to_string())),
+            "0143039947".parse::<Isbn>()
+        );
+        assert_eq!(
+            Err(IsbnError::InvalidChecksum("9780143039946".to_string())),
+            "9780143039946".parse::<Isbn>()
+        );
+        assert_eq!(
+            Err(IsbnError::InvalidFormat("12345".to_string())),
+            "12345".parse::<Isbn>()
+        );
+        assert_eq!(
+            Err(IsbnError::InvalidFormat("X143039946".to_string())),
+            "X143039946".parse::<Isbn>()
+        );
+        assert_eq!(
+            Err(IsbnError::InvalidFormat("01430399é".to_string())),
+            "01430399é".parse::<Isbn>()
+        );
+        assert_eq!(
+            Err(IsbnError::InvalidFormat("978014303994X".to_string())),
+            "978014303994X".parse::<Isbn>()
+        );
+    }
+}
@@ -1,28 +1,105 @@
+use std::sync::Arc;
+
 use crate::{
+    books_api::{GoogleBooks, MetadataCache, MetadataProviders, OpenLibrary},
+    config::Config,
     graphql::{graphiql, graphql_handler, Mutation, Query},
-    repository::BookRepository,
+    loader::{
+        AuthorCountsLoader, BookAuthorsLoader, BookSeriesLoader, BookTagsLoader, LibraryBookLoader,
+        NextInSeriesLoader, NotesLoader, ReadingProgressLoader, ReadingsLoader,
+        StatusHistoryLoader,
+    },
+    repository::{BookRepository, MemoryBookRepository, SqliteBookRepository, Storage},
 };
 use anyhow::Result;
-use async_graphql::{extensions::Logger, EmptySubscription, Schema};
+use async_graphql::{dataloader::DataLoader, extensions::Logger, EmptySubscription, Schema};
 use axum::{extract::Extension, routing::get, Router};
-use dotenvy::dotenv;
-use sqlx::{Pool, Postgres};
+use sqlx::{Pool, Postgres, Sqlite};
 use tokio::signal;
 use tower_http::trace::{self, TraceLayer};
 use tracing::warn;
 use tracing::Level;
 
 pub mod books_api;
+pub mod config;
 pub mod graphql;
+pub mod isbn;
+pub mod loader;
 pub mod repository;
 
-pub async fn generate_app(db_conn: Pool<Postgres>) -> Result<Router, Box<dyn std::error::Error>> {
-    dotenv().ok();
+/// Builds the app backed by a PostgreSQL database.
+pub async fn generate_app(
+    db_conn: Pool<Postgres>,
+    config: &Config,
+) -> Result<Router, Box<dyn std::error::Error>> {
+    let repository: Storage = Arc::new(BookRepository::new(db_conn));
+    build_app(repository, config)
+}
+
+/// Builds the app backed by a SQLite database.
+pub async fn generate_sqlite_app(
+    db_conn: Pool<Sqlite>,
+    config: &Config,
+) -> Result<Router, Box<dyn std::error::Error>> {
+    let repository: Storage = Arc::new(SqliteBookRepository::new(db_conn));
+    build_app(repository, config)
+}
+
+/// Builds the app backed by an in-memory store, e.g. `MemoryBookRepository::with_sample_data()`.
+/// Nothing is persisted once the app is dropped.
+pub async fn generate_memory_app(
+    repository: MemoryBookRepository,
+    config: &Config,
+) -> Result<Router, Box<dyn std::error::Error>> {
+    let repository: Storage = Arc::new(repository);
+    build_app(repository, config)
+}
 
-    let repository = BookRepository::new(db_conn);
+fn build_app(repository: Storage, config: &Config) -> Result<Router, Box<dyn std::error::Error>> {
+    let cache = MetadataCache::new(
+        config.metadata_cache_size,
+        config.metadata_cache_ttl,
+        config.metadata_cache_persistent.then(|| repository.clone()),
+    );
+    let providers = MetadataProviders::new(
+        Arc::new(GoogleBooks::new(
+            config.google_api_key.clone(),
+            config.google_books_api_url.clone(),
+        )),
+        Arc::new(OpenLibrary::new(config.open_library_api_url.clone())),
+        cache,
+    );
+    let notes_loader = DataLoader::new(NotesLoader::new(repository.clone()), tokio::spawn);
+    let book_authors_loader =
+        DataLoader::new(BookAuthorsLoader::new(repository.clone()), tokio::spawn);
+    let book_tags_loader = DataLoader::new(BookTagsLoader::new(repository.clone()), tokio::spawn);
+    let book_series_loader =
+        DataLoader::new(BookSeriesLoader::new(repository.clone()), tokio::spawn);
+    let next_in_series_loader =
+        DataLoader::new(NextInSeriesLoader::new(repository.clone()), tokio::spawn);
+    let author_counts_loader =
+        DataLoader::new(AuthorCountsLoader::new(repository.clone()), tokio::spawn);
+    let library_book_loader =
+        DataLoader::new(LibraryBookLoader::new(repository.clone()), tokio::spawn);
+    let status_history_loader =
+        DataLoader::new(StatusHistoryLoader::new(repository.clone()), tokio::spawn);
+    let readings_loader = DataLoader::new(ReadingsLoader::new(repository.clone()), tokio::spawn);
+    let reading_progress_loader =
+        DataLoader::new(ReadingProgressLoader::new(repository.clone()), tokio::spawn);
 
     let schema = Schema::build(Query, Mutation, EmptySubscription)
         .data(repository)
+        .data(providers)
+        .data(notes_loader)
+        .data(book_authors_loader)
+        .data(book_tags_loader)
+        .data(book_series_loader)
+        .data(next_in_series_loader)
+        .data(author_counts_loader)
+        .data(library_book_loader)
+        .data(status_history_loader)
+        .data(readings_loader)
+        .data(reading_progress_loader)
         .extension(Logger)
         .finish();
 
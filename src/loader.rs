@@ -0,0 +1,322 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_graphql::dataloader::Loader;
+use async_trait::async_trait;
+
+use crate::graphql::{Book, BookAuthor, Note, ProgressEntry, Reading, StatusEvent};
+use crate::repository::{
+    AuthorCounts, BookTag, LibraryBookKey, NextInSeries, SeriesEntry, Storage,
+};
+
+/// Batches `Book.notes` lookups so that resolving notes for a list of books costs one query
+/// instead of one per book.
+pub struct NotesLoader {
+    repository: Storage,
+}
+
+impl NotesLoader {
+    pub fn new(repository: Storage) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl Loader<i32> for NotesLoader {
+    type Value = Vec<Note>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, book_ids: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        let notes = self.repository.get_notes_by_books(book_ids).await?;
+
+        // Books without notes still get an entry so they resolve to an empty list.
+        let mut notes_by_book: HashMap<i32, Vec<Note>> = book_ids
+            .iter()
+            .map(|book_id| (*book_id, Vec::new()))
+            .collect();
+        for note in notes {
+            notes_by_book.entry(note.book_id).or_default().push(note);
+        }
+
+        Ok(notes_by_book)
+    }
+}
+
+/// Batches `Book.authors` lookups so that resolving credits for a list of books costs one query.
+pub struct BookAuthorsLoader {
+    repository: Storage,
+}
+
+impl BookAuthorsLoader {
+    pub fn new(repository: Storage) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl Loader<i32> for BookAuthorsLoader {
+    type Value = Vec<BookAuthor>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, book_ids: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        let credits = self.repository.get_book_authors(book_ids).await?;
+
+        let mut credits_by_book: HashMap<i32, Vec<BookAuthor>> = book_ids
+            .iter()
+            .map(|book_id| (*book_id, Vec::new()))
+            .collect();
+        for credit in credits {
+            credits_by_book
+                .entry(credit.book_id)
+                .or_default()
+                .push(credit);
+        }
+
+        Ok(credits_by_book)
+    }
+}
+
+/// Batches `Book.tags` lookups so that resolving tags for a list of books costs one query.
+pub struct BookTagsLoader {
+    repository: Storage,
+}
+
+impl BookTagsLoader {
+    pub fn new(repository: Storage) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl Loader<i32> for BookTagsLoader {
+    type Value = Vec<String>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, book_ids: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        let book_tags = self.repository.get_book_tags(book_ids).await?;
+
+        let mut tags_by_book: HashMap<i32, Vec<String>> = book_ids
+            .iter()
+            .map(|book_id| (*book_id, Vec::new()))
+            .collect();
+        for BookTag { book_id, tag } in book_tags {
+            tags_by_book.entry(book_id).or_default().push(tag);
+        }
+
+        Ok(tags_by_book)
+    }
+}
+
+/// Batches `Book.statusHistory` lookups so that resolving status history for a list of books costs
+/// one query.
+pub struct StatusHistoryLoader {
+    repository: Storage,
+}
+
+impl StatusHistoryLoader {
+    pub fn new(repository: Storage) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl Loader<i32> for StatusHistoryLoader {
+    type Value = Vec<StatusEvent>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, book_ids: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        let events = self.repository.get_status_history(book_ids).await?;
+
+        let mut events_by_book: HashMap<i32, Vec<StatusEvent>> = book_ids
+            .iter()
+            .map(|book_id| (*book_id, Vec::new()))
+            .collect();
+        for event in events {
+            events_by_book.entry(event.book_id).or_default().push(event);
+        }
+
+        Ok(events_by_book)
+    }
+}
+
+/// Batches `Book.readings`, `Book.startedAt` and `Book.finishedAt` lookups so that resolving them
+/// for a list of books costs one query.
+pub struct ReadingsLoader {
+    repository: Storage,
+}
+
+impl ReadingsLoader {
+    pub fn new(repository: Storage) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl Loader<i32> for ReadingsLoader {
+    type Value = Vec<Reading>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, book_ids: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        let readings = self.repository.get_readings(book_ids).await?;
+
+        let mut readings_by_book: HashMap<i32, Vec<Reading>> = book_ids
+            .iter()
+            .map(|book_id| (*book_id, Vec::new()))
+            .collect();
+        for reading in readings {
+            readings_by_book
+                .entry(reading.book_id)
+                .or_default()
+                .push(reading);
+        }
+
+        Ok(readings_by_book)
+    }
+}
+
+/// Batches `Reading.progress`, `Book.currentPage` and `Book.percentComplete` lookups so that
+/// resolving them for a list of readings costs one query.
+pub struct ReadingProgressLoader {
+    repository: Storage,
+}
+
+impl ReadingProgressLoader {
+    pub fn new(repository: Storage) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl Loader<i32> for ReadingProgressLoader {
+    type Value = Vec<ProgressEntry>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, reading_ids: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        let progress = self.repository.get_reading_progress(reading_ids).await?;
+
+        let mut progress_by_reading: HashMap<i32, Vec<ProgressEntry>> = reading_ids
+            .iter()
+            .map(|reading_id| (*reading_id, Vec::new()))
+            .collect();
+        for entry in progress {
+            progress_by_reading
+                .entry(entry.reading_id)
+                .or_default()
+                .push(entry);
+        }
+
+        Ok(progress_by_reading)
+    }
+}
+
+/// Batches `Book.series` and `Book.seriesPosition` lookups so that resolving them for a list of
+/// books costs one query.
+pub struct BookSeriesLoader {
+    repository: Storage,
+}
+
+impl BookSeriesLoader {
+    pub fn new(repository: Storage) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl Loader<i32> for BookSeriesLoader {
+    type Value = SeriesEntry;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, book_ids: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        let entries = self.repository.get_series_entries(book_ids).await?;
+
+        // Books that aren't in a series are left out, so they resolve to `None`.
+        Ok(entries
+            .into_iter()
+            .map(|entry| (entry.book_id, entry))
+            .collect())
+    }
+}
+
+/// Batches `Book.nextInSeries` lookups so that resolving the next book for a list of books costs
+/// one query.
+pub struct NextInSeriesLoader {
+    repository: Storage,
+}
+
+impl NextInSeriesLoader {
+    pub fn new(repository: Storage) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl Loader<i32> for NextInSeriesLoader {
+    type Value = Book;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, book_ids: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        let next_in_series = self.repository.get_next_in_series(book_ids).await?;
+
+        // Books with nothing to read next are left out, so they resolve to `None`.
+        Ok(next_in_series
+            .into_iter()
+            .map(|NextInSeries { book_id, next }| (book_id, next))
+            .collect())
+    }
+}
+
+/// Batches `Author.bookCount` and `Author.readCount` lookups so that counting books for a list of
+/// authors costs one query.
+pub struct AuthorCountsLoader {
+    repository: Storage,
+}
+
+impl AuthorCountsLoader {
+    pub fn new(repository: Storage) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl Loader<i32> for AuthorCountsLoader {
+    type Value = AuthorCounts;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, author_ids: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        let counts = self.repository.get_author_counts(author_ids).await?;
+
+        Ok(counts
+            .into_iter()
+            .map(|counts| (counts.author_id, counts))
+            .collect())
+    }
+}
+
+/// Batches `SearchResult.libraryBook` lookups so that a page of search results costs one query.
+pub struct LibraryBookLoader {
+    repository: Storage,
+}
+
+impl LibraryBookLoader {
+    pub fn new(repository: Storage) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl Loader<LibraryBookKey> for LibraryBookLoader {
+    type Value = Book;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(
+        &self,
+        keys: &[LibraryBookKey],
+    ) -> Result<HashMap<LibraryBookKey, Self::Value>, Self::Error> {
+        let books = self.repository.find_library_books(keys).await?;
+
+        // Search results that aren't in the library are left out, so they resolve to `None`.
+        Ok(keys
+            .iter()
+            .filter_map(|key| Some((key.clone(), key.find_match(&books)?.clone())))
+            .collect())
+    }
+}
@@ -1,28 +1,53 @@
-use std::net::SocketAddr;
-
 use anyhow::Result;
 use axum::Server;
-use borges::{generate_app, shutdown_signal};
-use dotenvy_macro::dotenv;
-use sqlx::PgPool;
+use borges::{config::Config, generate_app, generate_sqlite_app, shutdown_signal};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use sqlx::{postgres::PgConnectOptions, ConnectOptions};
 use tracing::info;
 use tracing::log::LevelFilter;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("Invalid configuration: {error}");
+            std::process::exit(1);
+        }
+    };
+
     tracing_subscriber::fmt()
         .with_target(false)
+        .with_max_level(config.log_level)
         .compact()
         .init();
 
-    let db_opts: PgConnectOptions = dotenv!("DATABASE_URL").parse()?;
-    let db = PgPool::connect_with(db_opts.log_statements(LevelFilter::Info)).await?;
+    // A `sqlite:` URL runs Borges from a single database file, which is created and migrated on
+    // startup. Anything else is treated as a PostgreSQL connection string.
+    let app = if config.database_url.starts_with("sqlite:") {
+        let db_opts: SqliteConnectOptions = config.database_url.parse()?;
+        let db = SqlitePoolOptions::new()
+            .max_connections(config.pool_size)
+            .connect_with(
+                db_opts
+                    .create_if_missing(true)
+                    .log_statements(LevelFilter::Info),
+            )
+            .await?;
+        sqlx::migrate!("./sqlite_migrations").run(&db).await?;
+        generate_sqlite_app(db, &config).await?
+    } else {
+        let db_opts: PgConnectOptions = config.database_url.parse()?;
+        let db = PgPoolOptions::new()
+            .max_connections(config.pool_size)
+            .connect_with(db_opts.log_statements(LevelFilter::Info))
+            .await?;
+        generate_app(db, &config).await?
+    };
 
-    let app = generate_app(db).await?;
-    let addr: SocketAddr = "0.0.0.0:8000".parse()?;
-    info!("Serving on {addr}");
-    Server::bind(&addr)
+    info!("Serving on {}", config.bind_address);
+    Server::bind(&config.bind_address)
         .serve(app.into_make_service())
         .with_graceful_shutdown(shutdown_signal())
         .await?;
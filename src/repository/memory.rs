@@ -0,0 +1,1422 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
+
+use super::{
+    check_progress_time, check_status_time, check_transition, merge_overridden_fields,
+    reading_change, status_filter, with_primary_author, AuthorCounts, BookStore, BookTag,
+    ExternalLink, KeysetPage, LibraryBookKey, NextInSeries, PageArgs, ReadingChange, SeriesEntry,
+    StatusChangeError,
+};
+use crate::graphql::{
+    AddBookInput, AddNoteInput, Author, Book, BookAuthor, BookAuthorInput, BookField, BookFilter,
+    BookSort, LibrarySearchHit, LibrarySearchMatch, Note, ProgressEntry, Reading, ReadingOutcome,
+    ReadingStatus, Series, Shelf, SortDirection, StatusEvent, UpdateBookInput, UpdateNoteInput,
+};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+/// In-memory implementation of `BookStore`, for tests and demos that shouldn't need a database.
+///
+/// Enforces the same rules as the SQL backends: title and author combinations are unique (the
+/// `book_title_author_key` constraint) and notes must reference an existing book.
+#[derive(Default)]
+pub struct MemoryBookRepository {
+    data: RwLock<MemoryData>,
+    latency: Option<Duration>,
+}
+
+#[derive(Default)]
+struct MemoryData {
+    books: Vec<Book>,
+    notes: Vec<Note>,
+    // Like `SERIAL` columns, IDs are never reused after a delete.
+    last_book_id: i32,
+    last_note_id: i32,
+    last_author_id: i32,
+    authors: Vec<Author>,
+    /// Every book's credits, in credit order within each book
+    book_authors: Vec<BookAuthor>,
+    book_tags: Vec<BookTag>,
+    last_shelf_id: i32,
+    shelves: Vec<Shelf>,
+    /// The IDs of the books on each shelf in shelf order, keyed by shelf ID
+    shelf_books: HashMap<i32, Vec<i32>>,
+    last_series_id: i32,
+    series: Vec<Series>,
+    /// The series ID and position of each book in a series, keyed by book ID
+    book_series: HashMap<i32, (i32, f64)>,
+    /// Every book's status changes, in the order they were recorded
+    status_events: Vec<StatusEvent>,
+    last_reading_id: i32,
+    readings: Vec<Reading>,
+    last_progress_id: i32,
+    /// Every reading's progress, in the order it was logged
+    reading_progress: Vec<ProgressEntry>,
+    /// The overridden fields of books added from a provider, keyed by book ID
+    overridden_fields: HashMap<i32, Vec<BookField>>,
+    /// Metadata provider responses and when they expire, keyed by request
+    cached_responses: HashMap<String, (String, i64)>,
+}
+
+impl MemoryBookRepository {
+    /// Creates an empty repository.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delays every operation by `latency` to simulate a round trip to a database server. The
+    /// delay happens outside of the data lock, so concurrent operations overlap like they would
+    /// against a connection pool.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// Creates a repository holding the same sample books and notes as the seed migrations.
+    pub fn with_sample_data() -> Self {
+        let repository = Self::new();
+        let mut data = repository.write();
+
+        let books = [
+            (
+                "Collected Fictions",
+                "Jorge Luis Borges",
+                1998,
+                565,
+                ReadingStatus::Read,
+            ),
+            (
+                "Gravity's Rainbow",
+                "Thomas Pynchon",
+                1973,
+                776,
+                ReadingStatus::Unread,
+            ),
+            (
+                "White Teeth",
+                "Zadie Smith",
+                2001,
+                464,
+                ReadingStatus::Unread,
+            ),
+            (
+                "Blood Meridian",
+                "Cormac McCarthy",
+                1985,
+                351,
+                ReadingStatus::Unread,
+            ),
+        ];
+        for (title, author, year, pages, status) in books {
+            data.insert_book(AddBookInput {
+                title: title.to_string(),
+                author: author.to_string(),
+                image_url: None,
+                year,
+                pages,
+                status: Some(status),
+                isbn_10: None,
+                isbn_13: None,
+                external_provider: None,
+                external_id: None,
+                authors: None,
+                overridden_fields: Vec::new(),
+            });
+        }
+
+        // Like the migration that added readings, books that have been read get an undated one.
+        data.insert_reading(1, None, None, Some(ReadingOutcome::Finished));
+
+        let notes = [
+            (1, "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore et dolore magna aliqua.", 100),
+            (2, "Ut enim ad minim veniam, quis nostrud exercitation ullamco laboris nisi ut aliquip ex ea commodo consequat.", 10),
+            (2, "Duis aute irure dolor in reprehenderit in voluptate velit esse cillum dolore eu fugiat nulla pariatur.", 420),
+        ];
+        for (book_id, note, page) in notes {
+            data.insert_note(AddNoteInput {
+                book_id,
+                note: note.to_string(),
+                page: Some(page),
+                reading_id: None,
+            });
+        }
+        drop(data);
+
+        repository
+    }
+
+    /// Waits out the simulated latency, if any.
+    async fn round_trip(&self) {
+        if let Some(latency) = self.latency {
+            tokio::time::sleep(latency).await;
+        }
+    }
+
+    // Lock poisoning only happens if a panic occurred while holding the lock, and none of the
+    // critical sections below can panic, so it's safe to keep using the data.
+    fn read(&self) -> RwLockReadGuard<'_, MemoryData> {
+        self.data.read().unwrap_or_else(|error| error.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, MemoryData> {
+        self.data.write().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+impl MemoryData {
+    fn insert_book(&mut self, input: AddBookInput) -> Book {
+        self.last_book_id += 1;
+        let credits = input.credits();
+        let book = Book {
+            id: self.last_book_id,
+            title: input.title,
+            author: input.author,
+            image_url: input.image_url,
+            year: input.year,
+            pages: input.pages,
+            status: input.status.unwrap_or_default(),
+            isbn_10: input.isbn_10,
+            isbn_13: input.isbn_13,
+            external_provider: input.external_provider,
+            external_id: input.external_id,
+        };
+        if book.external_id.is_some() {
+            self.overridden_fields
+                .insert(book.id, input.overridden_fields);
+        }
+        self.set_book_authors(book.id, &credits);
+        self.books.push(book.clone());
+        book
+    }
+
+    /// Replaces a book's credits, creating any authors that don't exist yet.
+    fn set_book_authors(&mut self, book_id: i32, credits: &[BookAuthorInput]) {
+        self.book_authors.retain(|credit| credit.book_id != book_id);
+        for credit in credits {
+            let author = match self
+                .authors
+                .iter()
+                .find(|author| author.name == credit.name)
+            {
+                Some(author) => author.clone(),
+                None => {
+                    self.last_author_id += 1;
+                    let author = Author {
+                        id: self.last_author_id,
+                        name: credit.name.clone(),
+                    };
+                    self.authors.push(author.clone());
+                    author
+                }
+            };
+            self.book_authors.push(BookAuthor {
+                book_id,
+                author,
+                role: credit.role,
+            });
+        }
+    }
+
+    /// The books in a series, ordered by position and then ID.
+    fn series_books(&self, series_id: i32) -> Vec<&Book> {
+        let mut books: Vec<(&Book, f64)> = self
+            .books
+            .iter()
+            .filter_map(|book| match self.book_series.get(&book.id) {
+                Some((id, position)) if *id == series_id => Some((book, *position)),
+                _ => None,
+            })
+            .collect();
+        books.sort_by(|(a, a_position), (b, b_position)| {
+            a_position.total_cmp(b_position).then(a.id.cmp(&b.id))
+        });
+        books.into_iter().map(|(book, _)| book).collect()
+    }
+
+    fn insert_note(&mut self, input: AddNoteInput) -> Note {
+        self.last_note_id += 1;
+        let note = Note {
+            id: self.last_note_id,
+            book_id: input.book_id,
+            note: input.note,
+            page: input.page,
+            reading_id: input.reading_id,
+        };
+        self.notes.push(note.clone());
+        note
+    }
+
+    fn insert_reading(
+        &mut self,
+        book_id: i32,
+        started_at: Option<DateTime<Utc>>,
+        finished_at: Option<DateTime<Utc>>,
+        outcome: Option<ReadingOutcome>,
+    ) {
+        self.last_reading_id += 1;
+        self.readings.push(Reading {
+            id: self.last_reading_id,
+            book_id,
+            started_at,
+            finished_at,
+            outcome,
+        });
+    }
+
+    /// The index of a book in `books`. Returns an error if there's no book with the ID.
+    fn book_index(&self, book_id: i32) -> Result<usize> {
+        self.books
+            .iter()
+            .position(|book| book.id == book_id)
+            .ok_or_else(|| anyhow!("No book with ID {} found.", book_id))
+    }
+
+    /// When a book's status last changed, if it ever has.
+    fn last_status_change(&self, book_id: i32) -> Option<DateTime<Utc>> {
+        self.status_events
+            .iter()
+            .filter(|event| event.book_id == book_id)
+            .map(|event| event.at)
+            .max()
+    }
+
+    /// Moves the book at `index` to `status` as of `at`, as `update_book_status` describes.
+    fn change_status(
+        &mut self,
+        index: usize,
+        status: ReadingStatus,
+        at: DateTime<Utc>,
+    ) -> Result<Book> {
+        let book_id = self.books[index].id;
+        let previous = self.books[index].status;
+        // Setting the status a book already has isn't a change, so its history is left alone.
+        if previous == status {
+            return Ok(self.books[index].clone());
+        }
+        check_transition(previous, status)?;
+        check_status_time(self.last_status_change(book_id), at)?;
+
+        self.books[index].status = status;
+        self.status_events.push(StatusEvent {
+            book_id,
+            status,
+            at,
+        });
+        if let Some(change) = reading_change(previous, status) {
+            self.apply_reading_change(book_id, change, at);
+        }
+        Ok(self.books[index].clone())
+    }
+
+    /// Starts, ends or drops one of a book's readings.
+    fn apply_reading_change(&mut self, book_id: i32, change: ReadingChange, at: DateTime<Utc>) {
+        let in_progress = self
+            .readings
+            .iter()
+            .position(|reading| reading.book_id == book_id && reading.outcome.is_none());
+        match (change, in_progress) {
+            (ReadingChange::Start, _) => self.insert_reading(book_id, Some(at), None, None),
+            (ReadingChange::End(outcome), Some(index)) => {
+                self.readings[index].finished_at = Some(at);
+                self.readings[index].outcome = Some(outcome);
+            }
+            (ReadingChange::End(outcome), None) => {
+                self.insert_reading(book_id, None, Some(at), Some(outcome))
+            }
+            (ReadingChange::Discard, Some(index)) => {
+                let reading = self.readings.remove(index);
+                self.reading_progress
+                    .retain(|entry| entry.reading_id != reading.id);
+                for note in &mut self.notes {
+                    if note.reading_id == Some(reading.id) {
+                        note.reading_id = None;
+                    }
+                }
+            }
+            (ReadingChange::Discard, None) => {}
+        }
+    }
+}
+
+#[async_trait]
+impl BookStore for MemoryBookRepository {
+    async fn get_book_by_title(&self, title: String) -> Result<Option<Book>> {
+        self.round_trip().await;
+        Ok(self
+            .read()
+            .books
+            .iter()
+            .find(|book| book.title == title)
+            .cloned())
+    }
+
+    async fn get_book_by_id(&self, book_id: i32) -> Result<Option<Book>> {
+        self.round_trip().await;
+        Ok(self
+            .read()
+            .books
+            .iter()
+            .find(|book| book.id == book_id)
+            .cloned())
+    }
+
+    async fn get_book_by_isbn(&self, isbn_13: String) -> Result<Option<Book>> {
+        self.round_trip().await;
+        Ok(self
+            .read()
+            .books
+            .iter()
+            .find(|book| book.isbn_13.as_ref() == Some(&isbn_13))
+            .cloned())
+    }
+
+    async fn get_books(
+        &self,
+        filter: BookFilter,
+        sort: BookSort,
+        direction: SortDirection,
+    ) -> Result<Option<Vec<Book>>> {
+        self.round_trip().await;
+        let data = self.read();
+        let mut books: Vec<Book> = data
+            .books
+            .iter()
+            .filter(|book| matches_filter(&data, book, &filter))
+            .cloned()
+            .collect();
+
+        books.sort_by(|a, b| {
+            let ordering = match sort {
+                BookSort::Title => a.title.cmp(&b.title),
+                BookSort::Author => a.author.cmp(&b.author),
+                BookSort::Year => a.year.cmp(&b.year),
+                BookSort::Pages => a.pages.cmp(&b.pages),
+                BookSort::DateAdded => Ordering::Equal,
+            };
+            ordering.then(a.id.cmp(&b.id))
+        });
+        if direction == SortDirection::Descending {
+            books.reverse();
+        }
+
+        Ok(Some(books))
+    }
+
+    async fn get_books_page(
+        &self,
+        status: Option<ReadingStatus>,
+        page: PageArgs,
+    ) -> Result<KeysetPage<Book>> {
+        self.round_trip().await;
+        let data = self.read();
+        let statuses = status_filter(status);
+        let books = data
+            .books
+            .iter()
+            .filter(|book| statuses.contains(&book.status));
+
+        Ok(KeysetPage::from_rows(
+            keyset_rows(books, |book| book.id, page),
+            page,
+        ))
+    }
+
+    async fn add_book(&self, input: AddBookInput) -> Result<Book> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        if data
+            .books
+            .iter()
+            .any(|book| book.title == input.title && book.author == input.author)
+        {
+            return Err(anyhow!(
+                "This book title and author combination has already been added."
+            ));
+        }
+        let same_isbn =
+            |book_isbn: &Option<String>, isbn: &Option<String>| isbn.is_some() && book_isbn == isbn;
+        if data.books.iter().any(|book| {
+            same_isbn(&book.isbn_10, &input.isbn_10) || same_isbn(&book.isbn_13, &input.isbn_13)
+        }) {
+            return Err(anyhow!("A book with this ISBN has already been added."));
+        }
+        if input.external_id.is_some()
+            && data.books.iter().any(|book| {
+                book.external_provider == input.external_provider
+                    && book.external_id == input.external_id
+            })
+        {
+            return Err(anyhow!(
+                "This book has already been added from its provider."
+            ));
+        }
+
+        let book = data.insert_book(input);
+        data.status_events.push(StatusEvent {
+            book_id: book.id,
+            status: book.status,
+            at: Utc::now(),
+        });
+        Ok(book)
+    }
+
+    async fn update_book(&self, input: UpdateBookInput) -> Result<Book> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        let index = data
+            .books
+            .iter()
+            .position(|book| book.id == input.book_id)
+            .ok_or_else(|| anyhow!("No book with ID {} found.", input.book_id))?;
+        let title = input
+            .title
+            .unwrap_or_else(|| data.books[index].title.clone());
+        let author_changed = input.author.is_some();
+        let author = input
+            .author
+            .unwrap_or_else(|| data.books[index].author.clone());
+        if data
+            .books
+            .iter()
+            .any(|book| book.id != input.book_id && book.title == title && book.author == author)
+        {
+            return Err(anyhow!(
+                "This book title and author combination has already been added."
+            ));
+        }
+
+        // Setting just `author` replaces the primary credit and keeps the others.
+        let credits = match (input.authors, author_changed) {
+            (Some(credits), _) => Some(credits),
+            (None, true) => {
+                let credits = data
+                    .book_authors
+                    .iter()
+                    .filter(|credit| credit.book_id == input.book_id)
+                    .map(|credit| BookAuthorInput {
+                        name: credit.author.name.clone(),
+                        role: credit.role,
+                    })
+                    .collect();
+                Some(with_primary_author(credits, &author))
+            }
+            (None, false) => None,
+        };
+        if let Some(credits) = &credits {
+            data.set_book_authors(input.book_id, credits);
+        }
+        if let Some(overridden_fields) = data.overridden_fields.get_mut(&input.book_id) {
+            merge_overridden_fields(overridden_fields, &input.overridden_fields);
+        }
+        let book = &mut data.books[index];
+        book.title = title;
+        book.author = author;
+        input.image_url.update_to(&mut book.image_url);
+        book.year = input.year.unwrap_or(book.year);
+        book.pages = input.pages.unwrap_or(book.pages);
+
+        Ok(book.clone())
+    }
+
+    async fn delete_book(&self, book_id: i32) -> Result<()> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        if !data.books.iter().any(|book| book.id == book_id) {
+            return Err(anyhow!("No book with ID {} found.", book_id));
+        }
+        data.books.retain(|book| book.id != book_id);
+        data.notes.retain(|note| note.book_id != book_id);
+        data.book_authors.retain(|credit| credit.book_id != book_id);
+        data.book_tags
+            .retain(|book_tag| book_tag.book_id != book_id);
+        for book_ids in data.shelf_books.values_mut() {
+            book_ids.retain(|id| *id != book_id);
+        }
+        data.book_series.remove(&book_id);
+        data.status_events.retain(|event| event.book_id != book_id);
+        let reading_ids: Vec<i32> = data
+            .readings
+            .iter()
+            .filter(|reading| reading.book_id == book_id)
+            .map(|reading| reading.id)
+            .collect();
+        data.reading_progress
+            .retain(|entry| !reading_ids.contains(&entry.reading_id));
+        data.readings.retain(|reading| reading.book_id != book_id);
+        data.overridden_fields.remove(&book_id);
+
+        Ok(())
+    }
+
+    async fn update_book_status(
+        &self,
+        book_id: i32,
+        status: ReadingStatus,
+        at: DateTime<Utc>,
+    ) -> Result<Book> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        let index = data.book_index(book_id)?;
+        data.change_status(index, status, at)
+    }
+
+    async fn start_rereading(&self, book_id: i32, at: DateTime<Utc>) -> Result<Book> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        let index = data.book_index(book_id)?;
+        if data.books[index].status != ReadingStatus::Read {
+            return Err(StatusChangeError::NotRead.into());
+        }
+        data.change_status(index, ReadingStatus::Reading, at)
+    }
+
+    async fn get_readings(&self, book_ids: &[i32]) -> Result<Vec<Reading>> {
+        self.round_trip().await;
+        let mut readings: Vec<Reading> = self
+            .read()
+            .readings
+            .iter()
+            .filter(|reading| book_ids.contains(&reading.book_id))
+            .cloned()
+            .collect();
+        readings.sort_by_key(|reading| (reading.book_id, reading.id));
+
+        Ok(readings)
+    }
+
+    async fn get_reading_by_id(&self, reading_id: i32) -> Result<Option<Reading>> {
+        self.round_trip().await;
+        Ok(self
+            .read()
+            .readings
+            .iter()
+            .find(|reading| reading.id == reading_id)
+            .cloned())
+    }
+
+    async fn log_progress(
+        &self,
+        book_id: i32,
+        page: i32,
+        at: DateTime<Utc>,
+    ) -> Result<(Book, ProgressEntry)> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        let index = data.book_index(book_id)?;
+        let reading_id = data
+            .readings
+            .iter()
+            .find(|reading| reading.book_id == book_id && reading.outcome.is_none())
+            .map(|reading| reading.id);
+        let Some(reading_id) =
+            reading_id.filter(|_| data.books[index].status == ReadingStatus::Reading)
+        else {
+            return Err(StatusChangeError::NotReading.into());
+        };
+        check_progress_time(data.last_status_change(book_id), at)?;
+
+        data.last_progress_id += 1;
+        let entry = ProgressEntry {
+            id: data.last_progress_id,
+            reading_id,
+            page,
+            at,
+        };
+        data.reading_progress.push(entry.clone());
+        // Reaching the last page finishes the book.
+        let book = if page == data.books[index].pages {
+            data.change_status(index, ReadingStatus::Read, at)?
+        } else {
+            data.books[index].clone()
+        };
+
+        Ok((book, entry))
+    }
+
+    async fn get_reading_progress(&self, reading_ids: &[i32]) -> Result<Vec<ProgressEntry>> {
+        self.round_trip().await;
+        let mut progress: Vec<ProgressEntry> = self
+            .read()
+            .reading_progress
+            .iter()
+            .filter(|entry| reading_ids.contains(&entry.reading_id))
+            .cloned()
+            .collect();
+        // IDs are assigned in logging order, so they break ties between entries at the same time.
+        progress.sort_by_key(|entry| (entry.reading_id, entry.at, entry.id));
+
+        Ok(progress)
+    }
+
+    async fn get_status_history(&self, book_ids: &[i32]) -> Result<Vec<StatusEvent>> {
+        self.round_trip().await;
+        let mut events: Vec<StatusEvent> = self
+            .read()
+            .status_events
+            .iter()
+            .filter(|event| book_ids.contains(&event.book_id))
+            .cloned()
+            .collect();
+        // The sort is stable, so changes at the same time stay in the order they were recorded.
+        events.sort_by(|a, b| a.book_id.cmp(&b.book_id).then(a.at.cmp(&b.at)));
+
+        Ok(events)
+    }
+
+    async fn get_notes_by_book(&self, book_id: i32) -> Result<Option<Vec<Note>>> {
+        self.round_trip().await;
+        let notes = self
+            .read()
+            .notes
+            .iter()
+            .filter(|note| note.book_id == book_id)
+            .cloned()
+            .collect();
+
+        Ok(Some(notes))
+    }
+
+    async fn get_book_authors(&self, book_ids: &[i32]) -> Result<Vec<BookAuthor>> {
+        self.round_trip().await;
+        let mut credits: Vec<BookAuthor> = self
+            .read()
+            .book_authors
+            .iter()
+            .filter(|credit| book_ids.contains(&credit.book_id))
+            .cloned()
+            .collect();
+        // A stable sort keeps each book's credits in order.
+        credits.sort_by_key(|credit| credit.book_id);
+
+        Ok(credits)
+    }
+
+    async fn get_books_by_author(&self, author_id: i32) -> Result<Vec<Book>> {
+        self.round_trip().await;
+        let data = self.read();
+        let mut books: Vec<Book> = data
+            .books
+            .iter()
+            .filter(|book| {
+                data.book_authors
+                    .iter()
+                    .any(|credit| credit.book_id == book.id && credit.author.id == author_id)
+            })
+            .cloned()
+            .collect();
+        books.sort_by_key(|book| book.id);
+
+        Ok(books)
+    }
+
+    async fn get_authors(&self) -> Result<Vec<Author>> {
+        self.round_trip().await;
+        let mut authors = self.read().authors.clone();
+        authors.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(authors)
+    }
+
+    async fn get_author_by_id(&self, author_id: i32) -> Result<Option<Author>> {
+        self.round_trip().await;
+        Ok(self
+            .read()
+            .authors
+            .iter()
+            .find(|author| author.id == author_id)
+            .cloned())
+    }
+
+    async fn get_author_counts(&self, author_ids: &[i32]) -> Result<Vec<AuthorCounts>> {
+        self.round_trip().await;
+        let data = self.read();
+
+        let counts = author_ids
+            .iter()
+            .filter_map(|author_id| {
+                let books: Vec<&Book> = data
+                    .books
+                    .iter()
+                    .filter(|book| {
+                        data.book_authors.iter().any(|credit| {
+                            credit.book_id == book.id && credit.author.id == *author_id
+                        })
+                    })
+                    .collect();
+                (!books.is_empty()).then(|| AuthorCounts {
+                    author_id: *author_id,
+                    books: books.len() as i64,
+                    read: books
+                        .iter()
+                        .filter(|book| book.status == ReadingStatus::Read)
+                        .count() as i64,
+                })
+            })
+            .collect();
+
+        Ok(counts)
+    }
+
+    async fn merge_authors(&self, source_ids: &[i32], target_id: i32) -> Result<Author> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        let find = |data: &MemoryData, author_id: i32| {
+            data.authors
+                .iter()
+                .find(|author| author.id == author_id)
+                .cloned()
+                .ok_or_else(|| anyhow!("No author with ID {} found.", author_id))
+        };
+        let target = find(&data, target_id)?;
+        let sources = source_ids
+            .iter()
+            .map(|source_id| find(&data, *source_id))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Check the renamed books before changing anything, so a failed merge changes nothing.
+        let renamed: Vec<i32> = data
+            .books
+            .iter()
+            .filter(|book| sources.iter().any(|source| source.name == book.author))
+            .map(|book| book.id)
+            .collect();
+        let mut titles_and_authors: Vec<(&str, &str)> = data
+            .books
+            .iter()
+            .map(|book| {
+                let author = if renamed.contains(&book.id) {
+                    &target.name
+                } else {
+                    &book.author
+                };
+                (book.title.as_str(), author.as_str())
+            })
+            .collect();
+        titles_and_authors.sort();
+        if titles_and_authors.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(anyhow!(
+                "This book title and author combination has already been added."
+            ));
+        }
+
+        for source in &sources {
+            // A source's credit is dropped if the target already has the same credit on the book.
+            let credits = &data.book_authors;
+            let repeated = |credit: &BookAuthor| {
+                credits.iter().any(|other| {
+                    other.book_id == credit.book_id
+                        && other.role == credit.role
+                        && other.author.id == target_id
+                })
+            };
+            data.book_authors = credits
+                .iter()
+                .filter(|credit| credit.author.id != source.id || !repeated(credit))
+                .map(|credit| BookAuthor {
+                    author: if credit.author.id == source.id {
+                        target.clone()
+                    } else {
+                        credit.author.clone()
+                    },
+                    ..credit.clone()
+                })
+                .collect();
+            data.authors.retain(|author| author.id != source.id);
+        }
+        for book in data.books.iter_mut() {
+            if renamed.contains(&book.id) {
+                book.author = target.name.clone();
+            }
+        }
+
+        Ok(target)
+    }
+
+    async fn get_book_tags(&self, book_ids: &[i32]) -> Result<Vec<BookTag>> {
+        self.round_trip().await;
+        let mut book_tags: Vec<BookTag> = self
+            .read()
+            .book_tags
+            .iter()
+            .filter(|book_tag| book_ids.contains(&book_tag.book_id))
+            .cloned()
+            .collect();
+        book_tags.sort_by(|a, b| a.book_id.cmp(&b.book_id).then(a.tag.cmp(&b.tag)));
+
+        Ok(book_tags)
+    }
+
+    async fn get_tags(&self) -> Result<Vec<String>> {
+        self.round_trip().await;
+        let mut tags: Vec<String> = self
+            .read()
+            .book_tags
+            .iter()
+            .map(|book_tag| book_tag.tag.clone())
+            .collect();
+        tags.sort();
+        tags.dedup();
+
+        Ok(tags)
+    }
+
+    async fn add_book_tags(&self, book_id: i32, tags: &[String]) -> Result<()> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        if !data.books.iter().any(|book| book.id == book_id) {
+            return Err(anyhow!("No book with ID {} found.", book_id));
+        }
+        for tag in tags {
+            let book_tag = BookTag {
+                book_id,
+                tag: tag.clone(),
+            };
+            if !data.book_tags.contains(&book_tag) {
+                data.book_tags.push(book_tag);
+            }
+        }
+        Ok(())
+    }
+
+    async fn remove_book_tags(&self, book_id: i32, tags: &[String]) -> Result<()> {
+        self.round_trip().await;
+        self.write()
+            .book_tags
+            .retain(|book_tag| book_tag.book_id != book_id || !tags.contains(&book_tag.tag));
+        Ok(())
+    }
+
+    async fn get_shelves(&self) -> Result<Vec<Shelf>> {
+        self.round_trip().await;
+        let mut shelves = self.read().shelves.clone();
+        shelves.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(shelves)
+    }
+
+    async fn get_shelf_by_id(&self, shelf_id: i32) -> Result<Option<Shelf>> {
+        self.round_trip().await;
+        Ok(self
+            .read()
+            .shelves
+            .iter()
+            .find(|shelf| shelf.id == shelf_id)
+            .cloned())
+    }
+
+    async fn create_shelf(&self, name: &str) -> Result<Shelf> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        if data.shelves.iter().any(|shelf| shelf.name == name) {
+            return Err(anyhow!("A shelf with this name already exists."));
+        }
+        data.last_shelf_id += 1;
+        let shelf = Shelf {
+            id: data.last_shelf_id,
+            name: name.to_string(),
+        };
+        data.shelves.push(shelf.clone());
+        data.shelf_books.insert(shelf.id, Vec::new());
+        Ok(shelf)
+    }
+
+    async fn rename_shelf(&self, shelf_id: i32, name: &str) -> Result<Shelf> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        if data
+            .shelves
+            .iter()
+            .any(|shelf| shelf.id != shelf_id && shelf.name == name)
+        {
+            return Err(anyhow!("A shelf with this name already exists."));
+        }
+        let shelf = data
+            .shelves
+            .iter_mut()
+            .find(|shelf| shelf.id == shelf_id)
+            .ok_or_else(|| anyhow!("No shelf with ID {} found.", shelf_id))?;
+        shelf.name = name.to_string();
+        Ok(shelf.clone())
+    }
+
+    async fn delete_shelf(&self, shelf_id: i32) -> Result<()> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        if !data.shelves.iter().any(|shelf| shelf.id == shelf_id) {
+            return Err(anyhow!("No shelf with ID {} found.", shelf_id));
+        }
+        data.shelves.retain(|shelf| shelf.id != shelf_id);
+        data.shelf_books.remove(&shelf_id);
+        Ok(())
+    }
+
+    async fn get_shelf_books(&self, shelf_id: i32) -> Result<Vec<Book>> {
+        self.round_trip().await;
+        let data = self.read();
+
+        let books = data
+            .shelf_books
+            .get(&shelf_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|book_id| data.books.iter().find(|book| book.id == *book_id))
+            .cloned()
+            .collect();
+        Ok(books)
+    }
+
+    async fn add_book_to_shelf(
+        &self,
+        shelf_id: i32,
+        book_id: i32,
+        position: Option<usize>,
+    ) -> Result<()> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        if !data.books.iter().any(|book| book.id == book_id) {
+            return Err(anyhow!("No book with ID {} found.", book_id));
+        }
+        let book_ids = data
+            .shelf_books
+            .get_mut(&shelf_id)
+            .ok_or_else(|| anyhow!("No shelf with ID {} found.", shelf_id))?;
+        book_ids.retain(|id| *id != book_id);
+        let position = position.unwrap_or(book_ids.len()).min(book_ids.len());
+        book_ids.insert(position, book_id);
+        Ok(())
+    }
+
+    async fn remove_book_from_shelf(&self, shelf_id: i32, book_id: i32) -> Result<()> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        let book_ids = data
+            .shelf_books
+            .get_mut(&shelf_id)
+            .filter(|book_ids| book_ids.contains(&book_id))
+            .ok_or_else(|| anyhow!("No book with ID {} found on that shelf.", book_id))?;
+        book_ids.retain(|id| *id != book_id);
+        Ok(())
+    }
+
+    async fn get_series_by_id(&self, series_id: i32) -> Result<Option<Series>> {
+        self.round_trip().await;
+        Ok(self
+            .read()
+            .series
+            .iter()
+            .find(|series| series.id == series_id)
+            .cloned())
+    }
+
+    async fn create_series(&self, name: &str) -> Result<Series> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        if data.series.iter().any(|series| series.name == name) {
+            return Err(anyhow!("A series with this name already exists."));
+        }
+        data.last_series_id += 1;
+        let series = Series {
+            id: data.last_series_id,
+            name: name.to_string(),
+        };
+        data.series.push(series.clone());
+        Ok(series)
+    }
+
+    async fn get_series_entries(&self, book_ids: &[i32]) -> Result<Vec<SeriesEntry>> {
+        self.round_trip().await;
+        let data = self.read();
+
+        let entries = book_ids
+            .iter()
+            .filter_map(|book_id| {
+                let (series_id, position) = data.book_series.get(book_id)?;
+                Some(SeriesEntry {
+                    book_id: *book_id,
+                    series: data
+                        .series
+                        .iter()
+                        .find(|series| series.id == *series_id)?
+                        .clone(),
+                    position: *position,
+                })
+            })
+            .collect();
+        Ok(entries)
+    }
+
+    async fn get_series_books(&self, series_id: i32) -> Result<Vec<Book>> {
+        self.round_trip().await;
+        let data = self.read();
+
+        Ok(data.series_books(series_id).into_iter().cloned().collect())
+    }
+
+    async fn set_book_series(&self, book_id: i32, series_id: i32, position: f64) -> Result<()> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        if !data.books.iter().any(|book| book.id == book_id) {
+            return Err(anyhow!("No book with ID {} found.", book_id));
+        }
+        if !data.series.iter().any(|series| series.id == series_id) {
+            return Err(anyhow!("No series with ID {} found.", series_id));
+        }
+        data.book_series.insert(book_id, (series_id, position));
+        Ok(())
+    }
+
+    async fn remove_book_from_series(&self, book_id: i32) -> Result<()> {
+        self.round_trip().await;
+        self.write()
+            .book_series
+            .remove(&book_id)
+            .ok_or_else(|| anyhow!("The book with ID {} isn't in a series.", book_id))?;
+        Ok(())
+    }
+
+    async fn get_next_in_series(&self, book_ids: &[i32]) -> Result<Vec<NextInSeries>> {
+        self.round_trip().await;
+        let data = self.read();
+
+        let mut next_in_series = Vec::new();
+        for book_id in book_ids {
+            let Some((series_id, position)) = data.book_series.get(book_id).copied() else {
+                continue;
+            };
+            let next = data
+                .series_books(series_id)
+                .into_iter()
+                .filter(|book| {
+                    let (_, other_position) = data.book_series[&book.id];
+                    other_position
+                        .total_cmp(&position)
+                        .then(book.id.cmp(book_id))
+                        .is_gt()
+                })
+                .find(|book| book.status == ReadingStatus::Unread);
+            if let Some(next) = next {
+                next_in_series.push(NextInSeries {
+                    book_id: *book_id,
+                    next: next.clone(),
+                });
+            }
+        }
+        Ok(next_in_series)
+    }
+
+    async fn get_notes_by_books(&self, book_ids: &[i32]) -> Result<Vec<Note>> {
+        self.round_trip().await;
+        let notes = self
+            .read()
+            .notes
+            .iter()
+            .filter(|note| book_ids.contains(&note.book_id))
+            .cloned()
+            .collect();
+
+        Ok(notes)
+    }
+
+    async fn get_notes_page(&self, book_id: i32, page: PageArgs) -> Result<KeysetPage<Note>> {
+        self.round_trip().await;
+        let data = self.read();
+        let notes = data.notes.iter().filter(|note| note.book_id == book_id);
+
+        Ok(KeysetPage::from_rows(
+            keyset_rows(notes, |note| note.id, page),
+            page,
+        ))
+    }
+
+    async fn get_note_by_id(&self, note_id: i32) -> Result<Option<Note>> {
+        self.round_trip().await;
+        Ok(self
+            .read()
+            .notes
+            .iter()
+            .find(|note| note.id == note_id)
+            .cloned())
+    }
+
+    async fn find_library_books(&self, keys: &[LibraryBookKey]) -> Result<Vec<Book>> {
+        self.round_trip().await;
+        Ok(self
+            .read()
+            .books
+            .iter()
+            .filter(|book| {
+                keys.iter()
+                    .any(|key| key.find_match(std::slice::from_ref(book)).is_some())
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn search_library(&self, query: String, limit: usize) -> Result<Vec<LibrarySearchHit>> {
+        self.round_trip().await;
+        let data = self.read();
+
+        // A simple stand-in for full-text search: every word of the query has to appear in the
+        // text, ignoring case, and hits are ranked by how many times the words appear.
+        let terms: Vec<String> = query
+            .split_whitespace()
+            .map(|term| term.to_ascii_lowercase())
+            .collect();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut hits = Vec::new();
+        for book in &data.books {
+            let text = format!("{} by {}", book.title, book.author);
+            if let Some((rank, snippet)) = search_text(&text, &terms) {
+                hits.push(LibrarySearchHit {
+                    matched: LibrarySearchMatch::Book,
+                    rank,
+                    snippet,
+                    book: book.clone(),
+                    note: None,
+                });
+            }
+        }
+        for note in &data.notes {
+            let Some((rank, snippet)) = search_text(&note.note, &terms) else {
+                continue;
+            };
+            if let Some(book) = data.books.iter().find(|book| book.id == note.book_id) {
+                hits.push(LibrarySearchHit {
+                    matched: LibrarySearchMatch::Note,
+                    rank,
+                    snippet,
+                    book: book.clone(),
+                    note: Some(note.clone()),
+                });
+            }
+        }
+
+        hits.sort_by(|a, b| {
+            b.rank
+                .total_cmp(&a.rank)
+                .then(a.book.id.cmp(&b.book.id))
+                .then(
+                    a.note
+                        .as_ref()
+                        .map(|note| note.id)
+                        .cmp(&b.note.as_ref().map(|note| note.id)),
+                )
+        });
+        hits.truncate(limit);
+
+        Ok(hits)
+    }
+
+    async fn add_note(&self, input: AddNoteInput) -> Result<Note> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        if !data.books.iter().any(|book| book.id == input.book_id) {
+            return Err(anyhow!("No book with ID {} found.", input.book_id));
+        }
+
+        Ok(data.insert_note(input))
+    }
+
+    async fn update_note(&self, input: UpdateNoteInput) -> Result<Note> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        let note = data
+            .notes
+            .iter_mut()
+            .find(|note| note.id == input.note_id)
+            .ok_or_else(|| anyhow!("No note with ID {} found.", input.note_id))?;
+        if let Some(content) = input.note {
+            note.note = content;
+        }
+        input.page.update_to(&mut note.page);
+        input.reading_id.update_to(&mut note.reading_id);
+
+        Ok(note.clone())
+    }
+
+    async fn delete_note(&self, note_id: i32) -> Result<()> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        if !data.notes.iter().any(|note| note.id == note_id) {
+            return Err(anyhow!("No note with ID {} found.", note_id));
+        }
+        data.notes.retain(|note| note.id != note_id);
+
+        Ok(())
+    }
+
+    async fn get_external_links(&self, book_id: Option<i32>) -> Result<Vec<ExternalLink>> {
+        self.round_trip().await;
+        let data = self.read();
+
+        let mut links: Vec<ExternalLink> = data
+            .books
+            .iter()
+            .filter(|book| book_id.is_none_or(|book_id| book.id == book_id))
+            .filter_map(|book| {
+                Some(ExternalLink {
+                    book_id: book.id,
+                    provider: book.external_provider?,
+                    external_id: book.external_id.clone()?,
+                    overridden_fields: data
+                        .overridden_fields
+                        .get(&book.id)
+                        .cloned()
+                        .unwrap_or_default(),
+                })
+            })
+            .collect();
+        links.sort_by_key(|link| link.book_id);
+        Ok(links)
+    }
+
+    async fn set_overridden_fields(&self, book_id: i32, fields: &[BookField]) -> Result<()> {
+        self.round_trip().await;
+        let mut data = self.write();
+
+        if let Some(overridden_fields) = data.overridden_fields.get_mut(&book_id) {
+            *overridden_fields = fields.to_vec();
+        }
+        Ok(())
+    }
+
+    async fn get_cached_response(&self, key: &str, now: i64) -> Result<Option<String>> {
+        self.round_trip().await;
+        Ok(self
+            .read()
+            .cached_responses
+            .get(key)
+            .filter(|(_, expires_at)| *expires_at > now)
+            .map(|(response, _)| response.clone()))
+    }
+
+    async fn put_cached_response(&self, key: &str, response: &str, expires_at: i64) -> Result<()> {
+        self.round_trip().await;
+        self.write()
+            .cached_responses
+            .insert(key.to_string(), (response.to_string(), expires_at));
+        Ok(())
+    }
+}
+
+/// Checks a book against a filter the same way the SQL backends do.
+fn matches_filter(data: &MemoryData, book: &Book, filter: &BookFilter) -> bool {
+    let contains = |haystack: &str, needle: &Option<String>| {
+        needle
+            .as_ref()
+            .is_none_or(|needle| haystack.to_lowercase().contains(&needle.to_lowercase()))
+    };
+
+    contains(&book.author, &filter.author)
+        && contains(&book.title, &filter.title)
+        && filter.min_year.is_none_or(|min_year| book.year >= min_year)
+        && filter.max_year.is_none_or(|max_year| book.year <= max_year)
+        && filter
+            .min_pages
+            .is_none_or(|min_pages| book.pages >= min_pages)
+        && filter
+            .max_pages
+            .is_none_or(|max_pages| book.pages <= max_pages)
+        && filter
+            .statuses
+            .as_deref()
+            .unwrap_or(&ReadingStatus::LIBRARY)
+            .contains(&book.status)
+        && filter.tags.as_ref().is_none_or(|tags| {
+            tags.iter().all(|tag| {
+                data.book_tags
+                    .iter()
+                    .any(|book_tag| book_tag.book_id == book.id && book_tag.tag == *tag)
+            })
+        })
+        && filter.shelf.is_none_or(|shelf_id| {
+            data.shelf_books
+                .get(&shelf_id)
+                .is_some_and(|book_ids| book_ids.contains(&book.id))
+        })
+}
+
+/// Matches lowercase search terms against `text`. If every term appears, returns the number of
+/// times the terms appear and `text` with the matches wrapped in `<b>` tags.
+fn search_text(text: &str, terms: &[String]) -> Option<(f64, String)> {
+    // ASCII lowercasing keeps byte offsets the same as in `text`.
+    let lowercase = text.to_ascii_lowercase();
+
+    let mut matches: Vec<(usize, usize)> = Vec::new();
+    for term in terms {
+        let found: Vec<(usize, usize)> = lowercase
+            .match_indices(term.as_str())
+            .map(|(start, term)| (start, start + term.len()))
+            .collect();
+        if found.is_empty() {
+            return None;
+        }
+        matches.extend(found);
+    }
+    let rank = matches.len() as f64;
+
+    // Merge overlapping matches before highlighting them.
+    matches.sort();
+    let mut highlights: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in matches {
+        match highlights.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end.max(*last_end),
+            _ => highlights.push((start, end)),
+        }
+    }
+
+    let mut snippet = String::new();
+    let mut position = 0;
+    for (start, end) in highlights {
+        snippet.push_str(&text[position..start]);
+        snippet.push_str("<b>");
+        snippet.push_str(&text[start..end]);
+        snippet.push_str("</b>");
+        position = end;
+    }
+    snippet.push_str(&text[position..]);
+
+    Some((rank, snippet))
+}
+
+/// Selects the rows for `page` the same way the SQL backends do: within the ID bounds, sorted by
+/// ID towards the requested end, and one row past the page size.
+fn keyset_rows<'a, T: Clone + 'a>(
+    rows: impl Iterator<Item = &'a T>,
+    id: impl Fn(&T) -> i32,
+    page: PageArgs,
+) -> Vec<T> {
+    let mut rows: Vec<T> = rows
+        .filter(|row| page.after.is_none_or(|after| id(row) > after))
+        .filter(|row| page.before.is_none_or(|before| id(row) < before))
+        .cloned()
+        .collect();
+    rows.sort_by_key(|row| id(row));
+    if page.from_end {
+        rows.reverse();
+    }
+    rows.truncate(page.limit + 1);
+    rows
+}
@@ -0,0 +1,733 @@
+use std::{fmt::Display, sync::Arc};
+
+use crate::graphql::{
+    AddBookInput, AddNoteInput, Author, AuthorRole, Book, BookAuthor, BookAuthorInput, BookField,
+    BookFilter, BookSort, ExternalProvider, LibrarySearchHit, LibrarySearchMatch, Note,
+    ProgressEntry, Reading, ReadingOutcome, ReadingStatus, Series, Shelf, SortDirection,
+    StatusEvent, UpdateBookInput, UpdateNoteInput,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+mod memory;
+mod postgres;
+mod sqlite;
+
+pub use memory::MemoryBookRepository;
+pub use postgres::BookRepository;
+pub use sqlite::SqliteBookRepository;
+
+pub type Storage = Arc<dyn BookStore>;
+
+/// Storage backend for books and notes. The GraphQL resolvers only depend on this trait, so any
+/// implementation can be plugged into the schema via `generate_app` and friends.
+///
+/// All methods take `&self` and implementations must be safe to call concurrently; the shared
+/// `Storage` handle is not wrapped in a lock, so resolvers never wait on each other.
+#[async_trait]
+pub trait BookStore: Send + Sync {
+    async fn get_book_by_title(&self, title: String) -> Result<Option<Book>>;
+
+    async fn get_book_by_id(&self, book_id: i32) -> Result<Option<Book>>;
+
+    /// Fetches a book by its ISBN-13. Books with an ISBN always have their ISBN-13 stored.
+    async fn get_book_by_isbn(&self, isbn_13: String) -> Result<Option<Book>>;
+
+    /// Fetches all books matching `filter`, sorted by `sort`. Ties are broken by ID in the same
+    /// direction.
+    async fn get_books(
+        &self,
+        filter: BookFilter,
+        sort: BookSort,
+        direction: SortDirection,
+    ) -> Result<Option<Vec<Book>>>;
+
+    /// Fetches a page of books ordered by ID, optionally limited to a single reading status.
+    async fn get_books_page(
+        &self,
+        status: Option<ReadingStatus>,
+        page: PageArgs,
+    ) -> Result<KeysetPage<Book>>;
+
+    /// Adds a book along with its credits, creating any authors that don't exist yet, and records
+    /// its status as set now. Returns an error if the title and author combination, an ISBN or the
+    /// external ID already exists.
+    async fn add_book(&self, input: AddBookInput) -> Result<Book>;
+
+    /// Updates the fields set in `input`, leaving the others unchanged. The book's credits are
+    /// replaced if `input.authors` is set, or else `input.author` replaces just its primary AUTHOR
+    /// credit. `input.overridden_fields` are added to those recorded on the book's provider link,
+    /// if it has one. Returns an error if the book doesn't exist or the new title and author
+    /// combination already exists.
+    async fn update_book(&self, input: UpdateBookInput) -> Result<Book>;
+
+    /// Deletes a book along with its notes. Returns an error if the book doesn't exist.
+    async fn delete_book(&self, book_id: i32) -> Result<()>;
+
+    /// Sets a book's reading status and records the change as happening `at`. Moving to READING
+    /// starts a reading, unless the book was PAUSED. Moving to READ or DID_NOT_FINISH finishes or
+    /// abandons the open reading, and moving back to UNREAD or WISHLIST drops it. Setting the status
+    /// the book already has changes nothing. Returns a `StatusChangeError` if the book can't move
+    /// to `status` or `at` is earlier than its last status change, or another error if the book
+    /// doesn't exist.
+    async fn update_book_status(
+        &self,
+        book_id: i32,
+        status: ReadingStatus,
+        at: DateTime<Utc>,
+    ) -> Result<Book>;
+
+    /// Moves a READ book back to READING as of `at`, starting a new reading. Returns a
+    /// `StatusChangeError` if the book isn't READ or `at` is earlier than its last status change,
+    /// or another error if the book doesn't exist.
+    async fn start_rereading(&self, book_id: i32, at: DateTime<Utc>) -> Result<Book>;
+
+    /// Fetches the status changes of several books at once, ordered by book ID and then time.
+    async fn get_status_history(&self, book_ids: &[i32]) -> Result<Vec<StatusEvent>>;
+
+    /// Fetches the readings of several books at once, ordered by book ID and then ID.
+    async fn get_readings(&self, book_ids: &[i32]) -> Result<Vec<Reading>>;
+
+    async fn get_reading_by_id(&self, reading_id: i32) -> Result<Option<Reading>>;
+
+    /// Records the page that a book's reading in progress has got to as of `at`, returning the
+    /// book along with the new entry. Reaching the book's last page marks it READ, which finishes
+    /// the reading, as part of the same change. Returns a `StatusChangeError` if the book isn't
+    /// being read or `at` is earlier than its last status change, or another error if the book
+    /// doesn't exist.
+    async fn log_progress(
+        &self,
+        book_id: i32,
+        page: i32,
+        at: DateTime<Utc>,
+    ) -> Result<(Book, ProgressEntry)>;
+
+    /// Fetches the progress of several readings at once, ordered by reading ID and then time.
+    async fn get_reading_progress(&self, reading_ids: &[i32]) -> Result<Vec<ProgressEntry>>;
+
+    async fn get_notes_by_book(&self, book_id: i32) -> Result<Option<Vec<Note>>>;
+
+    /// Fetches the credits for several books at once, ordered by book ID and then credit order.
+    async fn get_book_authors(&self, book_ids: &[i32]) -> Result<Vec<BookAuthor>>;
+
+    /// Fetches the books that credit an author in any role, ordered by ID.
+    async fn get_books_by_author(&self, author_id: i32) -> Result<Vec<Book>>;
+
+    /// Fetches every author, ordered by name.
+    async fn get_authors(&self) -> Result<Vec<Author>>;
+
+    async fn get_author_by_id(&self, author_id: i32) -> Result<Option<Author>>;
+
+    /// Counts the books crediting several authors at once. Authors without any books are left out.
+    async fn get_author_counts(&self, author_ids: &[i32]) -> Result<Vec<AuthorCounts>>;
+
+    /// Moves every credit from the source authors to the target author and deletes the sources,
+    /// all in one transaction. Books whose `author` is a source's name are renamed to the target,
+    /// and credits the target already has on a book are dropped rather than repeated. Returns an
+    /// error if any of the authors doesn't exist.
+    async fn merge_authors(&self, source_ids: &[i32], target_id: i32) -> Result<Author>;
+
+    /// Fetches the tags for several books at once, ordered by book ID and then tag.
+    async fn get_book_tags(&self, book_ids: &[i32]) -> Result<Vec<BookTag>>;
+
+    /// Fetches every tag that's on a book, in alphabetical order.
+    async fn get_tags(&self) -> Result<Vec<String>>;
+
+    /// Tags a book, ignoring tags it already has. Returns an error if the book doesn't exist.
+    async fn add_book_tags(&self, book_id: i32, tags: &[String]) -> Result<()>;
+
+    /// Removes tags from a book, ignoring tags it doesn't have.
+    async fn remove_book_tags(&self, book_id: i32, tags: &[String]) -> Result<()>;
+
+    /// Fetches every shelf, ordered by name.
+    async fn get_shelves(&self) -> Result<Vec<Shelf>>;
+
+    async fn get_shelf_by_id(&self, shelf_id: i32) -> Result<Option<Shelf>>;
+
+    /// Creates an empty shelf. Returns an error if a shelf with the name already exists.
+    async fn create_shelf(&self, name: &str) -> Result<Shelf>;
+
+    /// Returns an error if the shelf doesn't exist or another shelf has the name.
+    async fn rename_shelf(&self, shelf_id: i32, name: &str) -> Result<Shelf>;
+
+    /// Deletes a shelf, leaving its books in the library. Returns an error if the shelf doesn't
+    /// exist.
+    async fn delete_shelf(&self, shelf_id: i32) -> Result<()>;
+
+    /// Fetches the books on a shelf, in shelf order.
+    async fn get_shelf_books(&self, shelf_id: i32) -> Result<Vec<Book>>;
+
+    /// Puts a book on a shelf at a zero-based `position`, or at the end if `position` is `None`
+    /// or past the end. A book that's already on the shelf is moved. Returns an error if the
+    /// shelf or the book doesn't exist.
+    async fn add_book_to_shelf(
+        &self,
+        shelf_id: i32,
+        book_id: i32,
+        position: Option<usize>,
+    ) -> Result<()>;
+
+    /// Takes a book off a shelf, closing the gap it leaves. Returns an error if the book isn't on
+    /// the shelf.
+    async fn remove_book_from_shelf(&self, shelf_id: i32, book_id: i32) -> Result<()>;
+
+    async fn get_series_by_id(&self, series_id: i32) -> Result<Option<Series>>;
+
+    /// Creates an empty series. Returns an error if a series with the name already exists.
+    async fn create_series(&self, name: &str) -> Result<Series>;
+
+    /// Fetches the series entries for several books at once. Books that aren't in a series are
+    /// left out.
+    async fn get_series_entries(&self, book_ids: &[i32]) -> Result<Vec<SeriesEntry>>;
+
+    /// Fetches the books in a series, ordered by position and then ID.
+    async fn get_series_books(&self, series_id: i32) -> Result<Vec<Book>>;
+
+    /// Puts a book in a series at a position, taking it out of any series it was in. Returns an
+    /// error if the book or the series doesn't exist.
+    async fn set_book_series(&self, book_id: i32, series_id: i32, position: f64) -> Result<()>;
+
+    /// Takes a book out of its series. Returns an error if the book isn't in a series.
+    async fn remove_book_from_series(&self, book_id: i32) -> Result<()>;
+
+    /// Fetches the first unread book after each of several books in their series, by position and
+    /// then ID. Books with no unread book after them are left out. Only UNREAD books count, so
+    /// PAUSED and WISHLIST books are never returned.
+    async fn get_next_in_series(&self, book_ids: &[i32]) -> Result<Vec<NextInSeries>>;
+
+    /// Fetches the notes for several books at once, ordered by note ID.
+    async fn get_notes_by_books(&self, book_ids: &[i32]) -> Result<Vec<Note>>;
+
+    /// Fetches a page of a book's notes ordered by ID.
+    async fn get_notes_page(&self, book_id: i32, page: PageArgs) -> Result<KeysetPage<Note>>;
+
+    async fn get_note_by_id(&self, note_id: i32) -> Result<Option<Note>>;
+
+    /// Fetches the books that match any of `keys` by external ID, ISBN or title and author. Use
+    /// `LibraryBookKey::find_match` to pair the books back up with the keys.
+    async fn find_library_books(&self, keys: &[LibraryBookKey]) -> Result<Vec<Book>>;
+
+    /// Full-text searches book titles, authors and note text, returning up to `limit` hits with
+    /// the best matches first.
+    async fn search_library(&self, query: String, limit: usize) -> Result<Vec<LibrarySearchHit>>;
+
+    async fn add_note(&self, input: AddNoteInput) -> Result<Note>;
+
+    /// Updates the fields set in `input`, leaving the others unchanged. Returns an error if the
+    /// note doesn't exist.
+    async fn update_note(&self, input: UpdateNoteInput) -> Result<Note>;
+
+    /// Deletes a note. Returns an error if the note doesn't exist.
+    async fn delete_note(&self, note_id: i32) -> Result<()>;
+
+    /// Fetches the links between books and the providers they were added from, ordered by book
+    /// ID. Only fetches `book_id`'s link if it's given.
+    async fn get_external_links(&self, book_id: Option<i32>) -> Result<Vec<ExternalLink>>;
+
+    /// Replaces the fields recorded as overridden on a book's provider link. Does nothing if the
+    /// book has no link.
+    async fn set_overridden_fields(&self, book_id: i32, fields: &[BookField]) -> Result<()>;
+
+    /// Fetches a cached metadata provider response by its request key, unless it expired at or
+    /// before `now`. Times are in seconds since the Unix epoch.
+    async fn get_cached_response(&self, key: &str, now: i64) -> Result<Option<String>>;
+
+    /// Caches a metadata provider response under its request key until `expires_at`, replacing
+    /// any response already cached for the request.
+    async fn put_cached_response(&self, key: &str, response: &str, expires_at: i64) -> Result<()>;
+}
+
+/// A book's link to the metadata provider it was added from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExternalLink {
+    pub book_id: i32,
+    pub provider: ExternalProvider,
+    pub external_id: String,
+    /// The fields given in place of the provider's values, which refreshes leave alone
+    pub overridden_fields: Vec<BookField>,
+}
+
+/// How many books credit an author in any role, and how many of them have been read.
+#[derive(Clone, Debug, Default, PartialEq, sqlx::FromRow)]
+pub struct AuthorCounts {
+    pub author_id: i32,
+    pub books: i64,
+    pub read: i64,
+}
+
+/// A tag on a book.
+#[derive(Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct BookTag {
+    pub book_id: i32,
+    pub tag: String,
+}
+
+/// A book's place in a series.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeriesEntry {
+    pub book_id: i32,
+    pub series: Series,
+    pub position: f64,
+}
+
+/// The book to read after another one in its series.
+#[derive(Clone, Debug)]
+pub struct NextInSeries {
+    pub book_id: i32,
+    pub next: Book,
+}
+
+/// Keyset pagination arguments over rows ordered by ID.
+#[derive(Clone, Copy, Debug)]
+pub struct PageArgs {
+    /// Only include rows with an ID greater than this one
+    pub after: Option<i32>,
+    /// Only include rows with an ID less than this one
+    pub before: Option<i32>,
+    /// The maximum number of rows in the page
+    pub limit: usize,
+    /// Take the page from the end of the range (`last`) instead of the start (`first`)
+    pub from_end: bool,
+}
+
+impl PageArgs {
+    /// The number of rows to fetch: one more than the page size, to find out if there are more
+    /// rows past the page.
+    fn fetch_limit(&self) -> i64 {
+        self.limit as i64 + 1
+    }
+}
+
+/// A page of rows fetched with `PageArgs`, in ascending ID order.
+pub struct KeysetPage<T> {
+    pub items: Vec<T>,
+    pub has_previous_page: bool,
+    pub has_next_page: bool,
+}
+
+impl<T> KeysetPage<T> {
+    /// Builds a page from up to `page.fetch_limit()` rows, fetched in descending ID order when
+    /// paginating from the end and ascending order otherwise.
+    ///
+    /// As allowed by the Relay spec, `has_previous_page` is only computed when paginating
+    /// backwards and `has_next_page` only when paginating forwards.
+    fn from_rows(mut rows: Vec<T>, page: PageArgs) -> Self {
+        let has_more = rows.len() > page.limit;
+        rows.truncate(page.limit);
+        if page.from_end {
+            rows.reverse();
+        }
+
+        Self {
+            items: rows,
+            has_previous_page: page.from_end && has_more,
+            has_next_page: !page.from_end && has_more,
+        }
+    }
+}
+
+/// Identifies an external search result, to find the library's copy of it.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct LibraryBookKey {
+    pub provider: ExternalProvider,
+    pub external_id: String,
+    pub isbn_10: Option<String>,
+    pub isbn_13: Option<String>,
+    pub title: String,
+    /// The first author, which is the one stored when the book is added
+    pub author: Option<String>,
+}
+
+impl LibraryBookKey {
+    /// Finds the book matching this key, preferring a match by external ID, then by ISBN-13, then
+    /// ISBN-10, and finally by the `(title, author)` unique key.
+    pub fn find_match<'a>(&self, books: &'a [Book]) -> Option<&'a Book> {
+        books
+            .iter()
+            .find(|book| {
+                book.external_provider == Some(self.provider)
+                    && book.external_id.as_deref() == Some(self.external_id.as_str())
+            })
+            .or_else(|| {
+                books
+                    .iter()
+                    .find(|book| self.isbn_13.is_some() && book.isbn_13 == self.isbn_13)
+            })
+            .or_else(|| {
+                books
+                    .iter()
+                    .find(|book| self.isbn_10.is_some() && book.isbn_10 == self.isbn_10)
+            })
+            .or_else(|| {
+                books.iter().find(|book| {
+                    book.title == self.title && Some(&book.author) == self.author.as_ref()
+                })
+            })
+    }
+}
+
+/// A change to a book's status or reading that the book's current state doesn't allow. Returned by
+/// `BookStore` methods that make such changes, so that callers can tell it apart from a storage
+/// error.
+#[derive(Debug, PartialEq)]
+pub enum StatusChangeError {
+    /// The book's status can't go straight from `from` to `to`
+    Transition {
+        from: ReadingStatus,
+        to: ReadingStatus,
+    },
+    /// The change is earlier than the book's last one, which would put its history out of order
+    Backdated,
+    /// Only READ books can be reread
+    NotRead,
+    /// The book already has a reading in progress
+    AlreadyReading,
+    /// Progress can only be logged for a READING book's reading in progress
+    NotReading,
+    /// The progress is earlier than the book's last status change, which started its reading
+    ProgressBackdated,
+}
+
+impl Display for StatusChangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transition { from, to } => {
+                write!(f, "A book's status can't be changed from {from} to {to}.")
+            }
+            Self::Backdated => {
+                write!(
+                    f,
+                    "A status change can't be earlier than the book's last one."
+                )
+            }
+            Self::NotRead => write!(f, "Only books that have been read can be reread."),
+            Self::AlreadyReading => write!(f, "This book already has a reading in progress."),
+            Self::NotReading => write!(f, "Progress can only be logged for books being read."),
+            Self::ProgressBackdated => write!(
+                f,
+                "Progress can't be logged before the book's last status change."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StatusChangeError {}
+
+/// Returns a `StatusChangeError` if a book can't be moved from `previous` to `next`.
+fn check_transition(previous: ReadingStatus, next: ReadingStatus) -> Result<()> {
+    if !previous.can_transition_to(next) {
+        return Err(StatusChangeError::Transition {
+            from: previous,
+            to: next,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Returns a `StatusChangeError` if a change at `at` is earlier than the book's `last_change`.
+fn check_status_time(last_change: Option<DateTime<Utc>>, at: DateTime<Utc>) -> Result<()> {
+    if last_change.is_some_and(|last_change| at < last_change) {
+        return Err(StatusChangeError::Backdated.into());
+    }
+    Ok(())
+}
+
+/// Returns a `StatusChangeError` if progress at `at` is earlier than the book's `last_change`.
+fn check_progress_time(last_change: Option<DateTime<Utc>>, at: DateTime<Utc>) -> Result<()> {
+    if last_change.is_some_and(|last_change| at < last_change) {
+        return Err(StatusChangeError::ProgressBackdated.into());
+    }
+    Ok(())
+}
+
+/// The statuses to match when filtering books by an optional status.
+fn status_filter(status: Option<ReadingStatus>) -> Vec<ReadingStatus> {
+    match status {
+        Some(reading_state) => vec![reading_state],
+        None => ReadingStatus::LIBRARY.to_vec(),
+    }
+}
+
+/// What changing a book's reading status does to its readings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ReadingChange {
+    /// Starts a new reading
+    Start,
+    /// Ends the reading in progress with the outcome, or records an undated reading with the
+    /// outcome if there isn't one
+    End(ReadingOutcome),
+    /// Drops the reading in progress, if there is one
+    Discard,
+}
+
+/// How a change from `previous` to `next` affects a book's readings, if at all. Resuming a paused
+/// book carries on with the same reading.
+fn reading_change(previous: ReadingStatus, next: ReadingStatus) -> Option<ReadingChange> {
+    match next {
+        _ if previous == next => None,
+        ReadingStatus::Reading if previous != ReadingStatus::Paused => Some(ReadingChange::Start),
+        ReadingStatus::Read => Some(ReadingChange::End(ReadingOutcome::Finished)),
+        ReadingStatus::DidNotFinish => Some(ReadingChange::End(ReadingOutcome::DidNotFinish)),
+        ReadingStatus::Unread | ReadingStatus::Wishlist => Some(ReadingChange::Discard),
+        _ => None,
+    }
+}
+
+/// Makes `author` a book's primary credit, replacing the first credit if it's an AUTHOR one or else
+/// going before it. The other credits are kept, apart from an AUTHOR credit `author` already had.
+fn with_primary_author(mut credits: Vec<BookAuthorInput>, author: &str) -> Vec<BookAuthorInput> {
+    let primary = BookAuthorInput::author(author.to_string());
+    if credits
+        .first()
+        .is_some_and(|credit| credit.role == AuthorRole::Author)
+    {
+        credits.remove(0);
+    }
+    credits.retain(|credit| *credit != primary);
+    credits.insert(0, primary);
+    credits
+}
+
+/// Adds `fields` to a provider link's overridden fields, skipping any that are already there.
+fn merge_overridden_fields(overridden_fields: &mut Vec<BookField>, fields: &[BookField]) {
+    for field in fields {
+        if !overridden_fields.contains(field) {
+            overridden_fields.push(*field);
+        }
+    }
+}
+
+/// The `ORDER BY` clause for sorting books, shared by the SQL backends.
+fn book_order_by(sort: BookSort, direction: SortDirection) -> String {
+    let column = match sort {
+        BookSort::Title => "title",
+        BookSort::Author => "author",
+        BookSort::Year => "year",
+        BookSort::Pages => "pages",
+        // IDs are assigned in insertion order.
+        BookSort::DateAdded => "id",
+    };
+    let direction = match direction {
+        SortDirection::Ascending => "ASC",
+        SortDirection::Descending => "DESC",
+    };
+
+    if column == "id" {
+        format!(" ORDER BY id {direction}")
+    } else {
+        format!(" ORDER BY {column} {direction}, id {direction}")
+    }
+}
+
+/// SQL model of a `book_author` row joined with its `author`.
+#[derive(sqlx::FromRow)]
+struct SqlBookAuthor {
+    book_id: i32,
+    author_id: i32,
+    name: String,
+    role: AuthorRole,
+}
+
+impl SqlBookAuthor {
+    fn into_credit(self) -> BookAuthorInput {
+        BookAuthorInput {
+            name: self.name,
+            role: self.role,
+        }
+    }
+
+    fn into_book_author(self) -> BookAuthor {
+        BookAuthor {
+            book_id: self.book_id,
+            author: Author {
+                id: self.author_id,
+                name: self.name,
+            },
+            role: self.role,
+        }
+    }
+}
+
+/// SQL model of a `book_series` row joined with its `series`.
+#[derive(sqlx::FromRow)]
+struct SqlSeriesEntry {
+    book_id: i32,
+    series_id: i32,
+    name: String,
+    position: f64,
+}
+
+impl SqlSeriesEntry {
+    fn into_entry(self) -> SeriesEntry {
+        SeriesEntry {
+            book_id: self.book_id,
+            series: Series {
+                id: self.series_id,
+                name: self.name,
+            },
+            position: self.position,
+        }
+    }
+}
+
+/// SQL model representing the `book` table.
+#[derive(sqlx::FromRow)]
+struct SqlBook {
+    id: i32,
+    title: String,
+    author: String,
+    image_url: Option<String>,
+    year: i32,
+    pages: i32,
+    status: ReadingStatus,
+    isbn_10: Option<String>,
+    isbn_13: Option<String>,
+    external_provider: Option<ExternalProvider>,
+    external_id: Option<String>,
+}
+
+impl SqlBook {
+    /// Serializes a `SqlBook` database row to a Book domain model.
+    fn into_book(self) -> Book {
+        Book {
+            id: self.id,
+            title: self.title,
+            author: self.author,
+            image_url: self.image_url,
+            year: self.year,
+            pages: self.pages,
+            status: self.status,
+            isbn_10: self.isbn_10,
+            isbn_13: self.isbn_13,
+            external_provider: self.external_provider,
+            external_id: self.external_id,
+        }
+    }
+}
+
+/// A `book` row along with the book in the same series that it comes after.
+#[derive(sqlx::FromRow)]
+struct SqlNextInSeries {
+    previous_id: i32,
+    id: i32,
+    title: String,
+    author: String,
+    image_url: Option<String>,
+    year: i32,
+    pages: i32,
+    status: ReadingStatus,
+    isbn_10: Option<String>,
+    isbn_13: Option<String>,
+    external_provider: Option<ExternalProvider>,
+    external_id: Option<String>,
+}
+
+impl SqlNextInSeries {
+    fn into_next(self) -> NextInSeries {
+        let next = SqlBook {
+            id: self.id,
+            title: self.title,
+            author: self.author,
+            image_url: self.image_url,
+            year: self.year,
+            pages: self.pages,
+            status: self.status,
+            isbn_10: self.isbn_10,
+            isbn_13: self.isbn_13,
+            external_provider: self.external_provider,
+            external_id: self.external_id,
+        };
+        NextInSeries {
+            book_id: self.previous_id,
+            next: next.into_book(),
+        }
+    }
+}
+
+/// SQL model representing the `note` table.
+#[derive(sqlx::FromRow)]
+struct SqlNote {
+    id: i32,
+    book_id: i32,
+    note: String,
+    page: Option<i32>,
+    reading_id: Option<i32>,
+}
+
+impl SqlNote {
+    /// Serializes a `SqlNote` database row to a Note domain model.
+    fn into_note(self) -> Note {
+        Note {
+            id: self.id,
+            book_id: self.book_id,
+            note: self.note,
+            page: self.page,
+            reading_id: self.reading_id,
+        }
+    }
+}
+
+/// SQL model for a full-text search hit: the matching book, the matching note for note hits, and
+/// the hit's rank and snippet.
+#[derive(sqlx::FromRow)]
+struct SqlSearchHit {
+    id: i32,
+    title: String,
+    author: String,
+    image_url: Option<String>,
+    year: i32,
+    pages: i32,
+    status: ReadingStatus,
+    isbn_10: Option<String>,
+    isbn_13: Option<String>,
+    external_provider: Option<ExternalProvider>,
+    external_id: Option<String>,
+    note_id: Option<i32>,
+    note: Option<String>,
+    page: Option<i32>,
+    reading_id: Option<i32>,
+    rank: f64,
+    snippet: String,
+}
+
+impl SqlSearchHit {
+    /// Serializes a `SqlSearchHit` database row to a LibrarySearchHit domain model.
+    fn into_hit(self) -> LibrarySearchHit {
+        let note = match (self.note_id, self.note) {
+            (Some(id), Some(note)) => Some(Note {
+                id,
+                book_id: self.id,
+                note,
+                page: self.page,
+                reading_id: self.reading_id,
+            }),
+            _ => None,
+        };
+
+        LibrarySearchHit {
+            matched: if note.is_some() {
+                LibrarySearchMatch::Note
+            } else {
+                LibrarySearchMatch::Book
+            },
+            rank: self.rank,
+            snippet: self.snippet,
+            book: Book {
+                id: self.id,
+                title: self.title,
+                author: self.author,
+                image_url: self.image_url,
+                year: self.year,
+                pages: self.pages,
+                status: self.status,
+                isbn_10: self.isbn_10,
+                isbn_13: self.isbn_13,
+                external_provider: self.external_provider,
+                external_id: self.external_id,
+            },
+            note,
+        }
+    }
+}
@@ -0,0 +1,1233 @@
+use super::{
+    book_order_by, check_progress_time, check_status_time, check_transition,
+    merge_overridden_fields, reading_change, status_filter, with_primary_author, AuthorCounts,
+    BookStore, BookTag, ExternalLink, KeysetPage, LibraryBookKey, NextInSeries, PageArgs,
+    ReadingChange, SeriesEntry, SqlBook, SqlBookAuthor, SqlNextInSeries, SqlNote, SqlSearchHit,
+    SqlSeriesEntry, StatusChangeError,
+};
+use crate::graphql::{
+    AddBookInput, AddNoteInput, Author, Book, BookAuthor, BookAuthorInput, BookField, BookFilter,
+    BookSort, ExternalProvider, LibrarySearchHit, Note, ProgressEntry, Reading, ReadingStatus,
+    Series, Shelf, SortDirection, StatusEvent, UpdateBookInput, UpdateNoteInput,
+};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, Pool, Postgres, QueryBuilder};
+
+/// PostgreSQL implementation of `BookStore`.
+pub struct BookRepository {
+    pub db: Pool<Postgres>,
+}
+
+impl BookRepository {
+    pub fn new(conn: Pool<Postgres>) -> Self {
+        Self { db: conn }
+    }
+}
+
+#[async_trait]
+impl BookStore for BookRepository {
+    async fn get_book_by_title(&self, title: String) -> Result<Option<Book>> {
+        let row = sqlx::query_as!(
+            SqlBook,
+            r#"SELECT id, title, author, image_url, year, pages, status AS "status: _", isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS "external_provider: _", (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book WHERE title=$1"#,
+            title,
+        )
+        .fetch_optional(&self.db)
+        .await
+        .unwrap_or(None);
+
+        if let Some(result) = row {
+            return Ok(Some(result.into_book()));
+        }
+        Ok(None)
+    }
+
+    async fn get_book_by_id(&self, book_id: i32) -> Result<Option<Book>> {
+        let row = sqlx::query_as!(
+            SqlBook,
+            r#"SELECT id, title, author, image_url, year, pages, status AS "status: _", isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS "external_provider: _", (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book WHERE id=$1"#,
+            book_id,
+        )
+        .fetch_optional(&self.db)
+        .await
+        .unwrap_or(None);
+
+        if let Some(result) = row {
+            return Ok(Some(result.into_book()));
+        }
+        Ok(None)
+    }
+
+    async fn get_book_by_isbn(&self, isbn_13: String) -> Result<Option<Book>> {
+        let row = sqlx::query_as!(
+            SqlBook,
+            r#"SELECT id, title, author, image_url, year, pages, status AS "status: _", isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS "external_provider: _", (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book WHERE isbn_13=$1"#,
+            isbn_13,
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(row.map(SqlBook::into_book))
+    }
+
+    async fn get_books(
+        &self,
+        filter: BookFilter,
+        sort: BookSort,
+        direction: SortDirection,
+    ) -> Result<Option<Vec<Book>>> {
+        let mut query = QueryBuilder::<Postgres>::new(
+            "SELECT id, title, author, image_url, year, pages, status, isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS external_provider, (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book WHERE TRUE",
+        );
+        if let Some(author) = filter.author {
+            query
+                .push(" AND strpos(lower(author), lower(")
+                .push_bind(author)
+                .push(")) > 0");
+        }
+        if let Some(title) = filter.title {
+            query
+                .push(" AND strpos(lower(title), lower(")
+                .push_bind(title)
+                .push(")) > 0");
+        }
+        if let Some(min_year) = filter.min_year {
+            query.push(" AND year >= ").push_bind(min_year);
+        }
+        if let Some(max_year) = filter.max_year {
+            query.push(" AND year <= ").push_bind(max_year);
+        }
+        if let Some(min_pages) = filter.min_pages {
+            query.push(" AND pages >= ").push_bind(min_pages);
+        }
+        if let Some(max_pages) = filter.max_pages {
+            query.push(" AND pages <= ").push_bind(max_pages);
+        }
+        let statuses = filter.statuses.unwrap_or_else(|| status_filter(None));
+        query
+            .push(" AND status = ANY(")
+            .push_bind(statuses)
+            .push(")");
+        if let Some(mut tags) = filter.tags.filter(|tags| !tags.is_empty()) {
+            tags.sort();
+            tags.dedup();
+            query
+                .push(" AND id IN (SELECT book_id FROM book_tag WHERE tag = ANY(")
+                .push_bind(tags.clone())
+                .push(") GROUP BY book_id HAVING COUNT(*) = ")
+                .push_bind(tags.len() as i64)
+                .push(")");
+        }
+        if let Some(shelf_id) = filter.shelf {
+            query
+                .push(" AND id IN (SELECT book_id FROM shelf_book WHERE shelf_id = ")
+                .push_bind(shelf_id)
+                .push(")");
+        }
+        query.push(book_order_by(sort, direction));
+
+        let rows = query
+            .build_query_as::<SqlBook>()
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(Some(rows.into_iter().map(|row| row.into_book()).collect()))
+    }
+
+    async fn get_books_page(
+        &self,
+        status: Option<ReadingStatus>,
+        page: PageArgs,
+    ) -> Result<KeysetPage<Book>> {
+        let statuses = status_filter(status);
+
+        let rows = if page.from_end {
+            sqlx::query_as!(
+                SqlBook,
+                r#"SELECT id, title, author, image_url, year, pages, status AS "status: _", isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS "external_provider: _", (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book WHERE status = ANY($1) AND ($2::int IS NULL OR id > $2) AND ($3::int IS NULL OR id < $3) ORDER BY id DESC LIMIT $4"#,
+                statuses as _,
+                page.after,
+                page.before,
+                page.fetch_limit(),
+            )
+            .fetch_all(&self.db)
+            .await?
+        } else {
+            sqlx::query_as!(
+                SqlBook,
+                r#"SELECT id, title, author, image_url, year, pages, status AS "status: _", isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS "external_provider: _", (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book WHERE status = ANY($1) AND ($2::int IS NULL OR id > $2) AND ($3::int IS NULL OR id < $3) ORDER BY id ASC LIMIT $4"#,
+                statuses as _,
+                page.after,
+                page.before,
+                page.fetch_limit(),
+            )
+            .fetch_all(&self.db)
+            .await?
+        };
+
+        let books = rows.into_iter().map(|row| row.into_book()).collect();
+        Ok(KeysetPage::from_rows(books, page))
+    }
+
+    async fn add_book(&self, input: AddBookInput) -> Result<Book> {
+        let credits = input.credits();
+        let status = input.status.unwrap_or_default();
+        let mut tx = self.db.begin().await?;
+
+        let book_id = sqlx::query_scalar!(
+            "INSERT INTO book(title, author, image_url, year, pages, status, isbn_10, isbn_13) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING id",
+            input.title,
+            input.author,
+            input.image_url,
+            input.year,
+            input.pages,
+            status as _,
+            input.isbn_10,
+            input.isbn_13,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(book_write_error)?;
+
+        if let (Some(provider), Some(external_id)) = (input.external_provider, input.external_id) {
+            sqlx::query!(
+                "INSERT INTO book_external_id(book_id, provider, external_id, overridden_fields) VALUES ($1, $2, $3, $4)",
+                book_id,
+                provider as _,
+                external_id,
+                input.overridden_fields as _,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(book_write_error)?;
+        }
+        set_book_authors(&mut tx, book_id, &credits).await?;
+        add_status_event(&mut tx, book_id, status, Utc::now()).await?;
+
+        let row = sqlx::query_as!(
+            SqlBook,
+            r#"SELECT id, title, author, image_url, year, pages, status AS "status: _", isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS "external_provider: _", (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book WHERE id=$1"#,
+            book_id,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(row.into_book())
+    }
+
+    async fn update_book(&self, input: UpdateBookInput) -> Result<Book> {
+        let mut tx = self.db.begin().await?;
+
+        let set_image_url = !input.image_url.is_undefined();
+        let row = sqlx::query_as!(
+            SqlBook,
+            r#"UPDATE book SET title=COALESCE($2, title), author=COALESCE($3, author), image_url=CASE WHEN $4 THEN $5 ELSE image_url END, year=COALESCE($6, year), pages=COALESCE($7, pages) WHERE id=$1 RETURNING id, title, author, image_url, year, pages, status AS "status: _", isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS "external_provider: _", (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id"#,
+            input.book_id,
+            input.title,
+            input.author,
+            set_image_url,
+            input.image_url.take(),
+            input.year,
+            input.pages,
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(book_write_error)?
+        .ok_or_else(|| anyhow!("No book with ID {} found.", input.book_id))?;
+
+        // Setting just `author` replaces the primary credit and keeps the others.
+        let credits = match (input.authors, &input.author) {
+            (Some(credits), _) => Some(credits),
+            (None, Some(author)) => Some(with_primary_author(
+                book_credits(&mut tx, input.book_id).await?,
+                author,
+            )),
+            (None, None) => None,
+        };
+        if let Some(credits) = &credits {
+            set_book_authors(&mut tx, input.book_id, credits).await?;
+        }
+        if !input.overridden_fields.is_empty() {
+            add_overridden_fields(&mut tx, input.book_id, &input.overridden_fields).await?;
+        }
+        tx.commit().await?;
+
+        Ok(row.into_book())
+    }
+
+    async fn delete_book(&self, book_id: i32) -> Result<()> {
+        // Notes are removed by the `ON DELETE CASCADE` on `fk_book_id`.
+        let result = sqlx::query!("DELETE FROM book WHERE id=$1", book_id)
+            .execute(&self.db)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("No book with ID {} found.", book_id));
+        }
+        Ok(())
+    }
+
+    async fn update_book_status(
+        &self,
+        book_id: i32,
+        status: ReadingStatus,
+        at: DateTime<Utc>,
+    ) -> Result<Book> {
+        let mut tx = self.db.begin().await?;
+
+        let book = lock_book(&mut tx, book_id).await?;
+        let book = change_status(&mut tx, book, status, at).await?;
+        tx.commit().await?;
+
+        Ok(book)
+    }
+
+    async fn start_rereading(&self, book_id: i32, at: DateTime<Utc>) -> Result<Book> {
+        let mut tx = self.db.begin().await?;
+
+        let book = lock_book(&mut tx, book_id).await?;
+        if book.status != ReadingStatus::Read {
+            return Err(StatusChangeError::NotRead.into());
+        }
+        let book = change_status(&mut tx, book, ReadingStatus::Reading, at).await?;
+        tx.commit().await?;
+
+        Ok(book)
+    }
+
+    async fn get_readings(&self, book_ids: &[i32]) -> Result<Vec<Reading>> {
+        let rows = sqlx::query_as!(
+            Reading,
+            r#"SELECT id, book_id, started_at, finished_at, outcome AS "outcome: _" FROM reading WHERE book_id = ANY($1) ORDER BY book_id, id"#,
+            book_ids,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn get_reading_by_id(&self, reading_id: i32) -> Result<Option<Reading>> {
+        let row = sqlx::query_as!(
+            Reading,
+            r#"SELECT id, book_id, started_at, finished_at, outcome AS "outcome: _" FROM reading WHERE id=$1"#,
+            reading_id,
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn log_progress(
+        &self,
+        book_id: i32,
+        page: i32,
+        at: DateTime<Utc>,
+    ) -> Result<(Book, ProgressEntry)> {
+        let mut tx = self.db.begin().await?;
+
+        let book = lock_book(&mut tx, book_id).await?;
+        let reading_id = sqlx::query_scalar!(
+            "SELECT id FROM reading WHERE book_id=$1 AND outcome IS NULL",
+            book_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(reading_id) = reading_id.filter(|_| book.status == ReadingStatus::Reading) else {
+            return Err(StatusChangeError::NotReading.into());
+        };
+        check_progress_time(last_status_change(&mut tx, book_id).await?, at)?;
+
+        let entry = sqlx::query_as!(
+            ProgressEntry,
+            "INSERT INTO reading_progress(reading_id, page, logged_at) VALUES ($1, $2, $3) RETURNING id, reading_id, page, logged_at AS at",
+            reading_id,
+            page,
+            at,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        // Reaching the last page finishes the book.
+        let book = if page == book.pages {
+            change_status(&mut tx, book, ReadingStatus::Read, at).await?
+        } else {
+            book
+        };
+        tx.commit().await?;
+
+        Ok((book, entry))
+    }
+
+    async fn get_reading_progress(&self, reading_ids: &[i32]) -> Result<Vec<ProgressEntry>> {
+        let rows = sqlx::query_as!(
+            ProgressEntry,
+            "SELECT id, reading_id, page, logged_at AS at FROM reading_progress WHERE reading_id = ANY($1) ORDER BY reading_id, logged_at, id",
+            reading_ids,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn get_status_history(&self, book_ids: &[i32]) -> Result<Vec<StatusEvent>> {
+        let rows = sqlx::query_as!(
+            StatusEvent,
+            r#"SELECT book_id, status AS "status: _", changed_at AS at FROM status_event WHERE book_id = ANY($1) ORDER BY book_id, changed_at, id"#,
+            book_ids,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn get_notes_by_book(&self, book_id: i32) -> Result<Option<Vec<Note>>> {
+        let rows = sqlx::query_as!(
+            SqlNote,
+            "SELECT id, book_id, note, page, reading_id FROM note WHERE book_id=$1",
+            book_id,
+        )
+        .fetch_all(&self.db)
+        .await
+        .unwrap_or_default();
+
+        Ok(Some(rows.into_iter().map(|row| row.into_note()).collect()))
+    }
+
+    async fn get_book_authors(&self, book_ids: &[i32]) -> Result<Vec<BookAuthor>> {
+        let rows = sqlx::query_as!(
+            SqlBookAuthor,
+            r#"SELECT ba.book_id, ba.author_id, a.name, ba.role AS "role: _" FROM book_author ba JOIN author a ON a.id = ba.author_id WHERE ba.book_id = ANY($1) ORDER BY ba.book_id, ba.position"#,
+            book_ids,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_book_author()).collect())
+    }
+
+    async fn get_books_by_author(&self, author_id: i32) -> Result<Vec<Book>> {
+        let rows = sqlx::query_as!(
+            SqlBook,
+            r#"SELECT id, title, author, image_url, year, pages, status AS "status: _", isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS "external_provider: _", (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book WHERE id IN (SELECT book_id FROM book_author WHERE author_id=$1) ORDER BY id"#,
+            author_id,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_book()).collect())
+    }
+
+    async fn get_authors(&self) -> Result<Vec<Author>> {
+        let authors = sqlx::query_as!(Author, "SELECT id, name FROM author ORDER BY name")
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(authors)
+    }
+
+    async fn get_author_by_id(&self, author_id: i32) -> Result<Option<Author>> {
+        let author = sqlx::query_as!(Author, "SELECT id, name FROM author WHERE id=$1", author_id)
+            .fetch_optional(&self.db)
+            .await?;
+
+        Ok(author)
+    }
+
+    async fn get_author_counts(&self, author_ids: &[i32]) -> Result<Vec<AuthorCounts>> {
+        let counts = sqlx::query_as!(
+            AuthorCounts,
+            r#"SELECT ba.author_id, COUNT(DISTINCT ba.book_id) AS "books!", COUNT(DISTINCT ba.book_id) FILTER (WHERE b.status = 'read') AS "read!" FROM book_author ba JOIN book b ON b.id = ba.book_id WHERE ba.author_id = ANY($1) GROUP BY ba.author_id"#,
+            author_ids,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(counts)
+    }
+
+    async fn merge_authors(&self, source_ids: &[i32], target_id: i32) -> Result<Author> {
+        let mut tx = self.db.begin().await?;
+
+        let target = sqlx::query_as!(Author, "SELECT id, name FROM author WHERE id=$1", target_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| anyhow!("No author with ID {} found.", target_id))?;
+
+        for source_id in source_ids {
+            let source_name = sqlx::query_scalar!("SELECT name FROM author WHERE id=$1", source_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or_else(|| anyhow!("No author with ID {} found.", source_id))?;
+
+            sqlx::query!(
+                "DELETE FROM book_author s WHERE author_id=$1 AND EXISTS (SELECT 1 FROM book_author t WHERE t.book_id = s.book_id AND t.role = s.role AND t.author_id = $2)",
+                source_id,
+                target_id,
+            )
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query!(
+                "UPDATE book_author SET author_id=$2 WHERE author_id=$1",
+                source_id,
+                target_id,
+            )
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query!(
+                "UPDATE book SET author=$2 WHERE author=$1",
+                source_name,
+                target.name,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(book_write_error)?;
+            sqlx::query!("DELETE FROM author WHERE id=$1", source_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(target)
+    }
+
+    async fn get_book_tags(&self, book_ids: &[i32]) -> Result<Vec<BookTag>> {
+        let rows = sqlx::query_as!(
+            BookTag,
+            "SELECT book_id, tag FROM book_tag WHERE book_id = ANY($1) ORDER BY book_id, tag",
+            book_ids,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn get_tags(&self) -> Result<Vec<String>> {
+        let tags = sqlx::query_scalar!("SELECT DISTINCT tag FROM book_tag ORDER BY tag")
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(tags)
+    }
+
+    async fn add_book_tags(&self, book_id: i32, tags: &[String]) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        book_exists(&mut tx, book_id).await?;
+        sqlx::query!(
+            "INSERT INTO book_tag(book_id, tag) SELECT $1, UNNEST($2::text[]) ON CONFLICT DO NOTHING",
+            book_id,
+            tags,
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn remove_book_tags(&self, book_id: i32, tags: &[String]) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM book_tag WHERE book_id=$1 AND tag = ANY($2)",
+            book_id,
+            tags,
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_shelves(&self) -> Result<Vec<Shelf>> {
+        let shelves = sqlx::query_as!(Shelf, "SELECT id, name FROM shelf ORDER BY name")
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(shelves)
+    }
+
+    async fn get_shelf_by_id(&self, shelf_id: i32) -> Result<Option<Shelf>> {
+        let shelf = sqlx::query_as!(Shelf, "SELECT id, name FROM shelf WHERE id=$1", shelf_id)
+            .fetch_optional(&self.db)
+            .await?;
+
+        Ok(shelf)
+    }
+
+    async fn create_shelf(&self, name: &str) -> Result<Shelf> {
+        let shelf = sqlx::query_as!(
+            Shelf,
+            "INSERT INTO shelf(name) VALUES ($1) RETURNING id, name",
+            name,
+        )
+        .fetch_one(&self.db)
+        .await
+        .map_err(shelf_write_error)?;
+
+        Ok(shelf)
+    }
+
+    async fn rename_shelf(&self, shelf_id: i32, name: &str) -> Result<Shelf> {
+        sqlx::query_as!(
+            Shelf,
+            "UPDATE shelf SET name=$2 WHERE id=$1 RETURNING id, name",
+            shelf_id,
+            name,
+        )
+        .fetch_optional(&self.db)
+        .await
+        .map_err(shelf_write_error)?
+        .ok_or_else(|| anyhow!("No shelf with ID {} found.", shelf_id))
+    }
+
+    async fn delete_shelf(&self, shelf_id: i32) -> Result<()> {
+        // The shelf's books are taken off it by the `ON DELETE CASCADE` on `shelf_book`.
+        let result = sqlx::query!("DELETE FROM shelf WHERE id=$1", shelf_id)
+            .execute(&self.db)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("No shelf with ID {} found.", shelf_id));
+        }
+        Ok(())
+    }
+
+    async fn get_shelf_books(&self, shelf_id: i32) -> Result<Vec<Book>> {
+        let rows = sqlx::query_as!(
+            SqlBook,
+            r#"SELECT id, title, author, image_url, year, pages, status AS "status: _", isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS "external_provider: _", (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book JOIN shelf_book ON shelf_book.book_id = book.id WHERE shelf_book.shelf_id=$1 ORDER BY shelf_book.position"#,
+            shelf_id,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_book()).collect())
+    }
+
+    async fn add_book_to_shelf(
+        &self,
+        shelf_id: i32,
+        book_id: i32,
+        position: Option<usize>,
+    ) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        // Locking the shelf keeps concurrent changes to it from interleaving.
+        sqlx::query!("SELECT id FROM shelf WHERE id=$1 FOR UPDATE", shelf_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| anyhow!("No shelf with ID {} found.", shelf_id))?;
+        book_exists(&mut tx, book_id).await?;
+
+        // The shelf is rewritten in its new order, which keeps the positions contiguous.
+        let mut book_ids = sqlx::query_scalar!(
+            "SELECT book_id FROM shelf_book WHERE shelf_id=$1 AND book_id<>$2 ORDER BY position",
+            shelf_id,
+            book_id,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let position = position.unwrap_or(book_ids.len()).min(book_ids.len());
+        book_ids.insert(position, book_id);
+
+        sqlx::query!("DELETE FROM shelf_book WHERE shelf_id=$1", shelf_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            "INSERT INTO shelf_book(shelf_id, book_id, position) SELECT $1, book_id, (position - 1)::int FROM UNNEST($2::int[]) WITH ORDINALITY AS shelved(book_id, position)",
+            shelf_id,
+            &book_ids,
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn remove_book_from_shelf(&self, shelf_id: i32, book_id: i32) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        let position = sqlx::query_scalar!(
+            "DELETE FROM shelf_book WHERE shelf_id=$1 AND book_id=$2 RETURNING position",
+            shelf_id,
+            book_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| anyhow!("No book with ID {} found on that shelf.", book_id))?;
+        sqlx::query!(
+            "UPDATE shelf_book SET position = position - 1 WHERE shelf_id=$1 AND position > $2",
+            shelf_id,
+            position,
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_series_by_id(&self, series_id: i32) -> Result<Option<Series>> {
+        let series = sqlx::query_as!(Series, "SELECT id, name FROM series WHERE id=$1", series_id)
+            .fetch_optional(&self.db)
+            .await?;
+
+        Ok(series)
+    }
+
+    async fn create_series(&self, name: &str) -> Result<Series> {
+        let series = sqlx::query_as!(
+            Series,
+            "INSERT INTO series(name) VALUES ($1) RETURNING id, name",
+            name,
+        )
+        .fetch_one(&self.db)
+        .await
+        .map_err(|error| match error {
+            sqlx::Error::Database(error) if error.constraint() == Some("series_name_key") => {
+                anyhow!("A series with this name already exists.")
+            }
+            _ => anyhow!("There was an error connecting to the database."),
+        })?;
+
+        Ok(series)
+    }
+
+    async fn get_series_entries(&self, book_ids: &[i32]) -> Result<Vec<SeriesEntry>> {
+        let rows = sqlx::query_as!(
+            SqlSeriesEntry,
+            "SELECT bs.book_id, bs.series_id, s.name, bs.position FROM book_series bs JOIN series s ON s.id = bs.series_id WHERE bs.book_id = ANY($1)",
+            book_ids,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_entry()).collect())
+    }
+
+    async fn get_series_books(&self, series_id: i32) -> Result<Vec<Book>> {
+        let rows = sqlx::query_as!(
+            SqlBook,
+            r#"SELECT id, title, author, image_url, year, pages, status AS "status: _", isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS "external_provider: _", (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book JOIN book_series ON book_series.book_id = book.id WHERE book_series.series_id=$1 ORDER BY book_series.position, book.id"#,
+            series_id,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_book()).collect())
+    }
+
+    async fn set_book_series(&self, book_id: i32, series_id: i32, position: f64) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        book_exists(&mut tx, book_id).await?;
+        sqlx::query!("SELECT id FROM series WHERE id=$1", series_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| anyhow!("No series with ID {} found.", series_id))?;
+        sqlx::query!(
+            "INSERT INTO book_series(book_id, series_id, position) VALUES ($1, $2, $3) ON CONFLICT (book_id) DO UPDATE SET series_id=EXCLUDED.series_id, position=EXCLUDED.position",
+            book_id,
+            series_id,
+            position,
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn remove_book_from_series(&self, book_id: i32) -> Result<()> {
+        let result = sqlx::query!("DELETE FROM book_series WHERE book_id=$1", book_id)
+            .execute(&self.db)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("The book with ID {} isn't in a series.", book_id));
+        }
+        Ok(())
+    }
+
+    async fn get_next_in_series(&self, book_ids: &[i32]) -> Result<Vec<NextInSeries>> {
+        let rows = sqlx::query_as!(
+            SqlNextInSeries,
+            r#"SELECT DISTINCT ON (current.book_id) current.book_id AS previous_id, book.id, title, author, image_url, year, pages, book.status AS "status: _", isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS "external_provider: _", (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book JOIN book_series next ON next.book_id = book.id JOIN book_series current ON current.series_id = next.series_id WHERE current.book_id = ANY($1) AND (next.position, next.book_id) > (current.position, current.book_id) AND book.status = $2 ORDER BY current.book_id, next.position, next.book_id"#,
+            book_ids,
+            ReadingStatus::Unread as _,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_next()).collect())
+    }
+
+    async fn get_notes_by_books(&self, book_ids: &[i32]) -> Result<Vec<Note>> {
+        let rows = sqlx::query_as!(
+            SqlNote,
+            "SELECT id, book_id, note, page, reading_id FROM note WHERE book_id = ANY($1) ORDER BY id",
+            book_ids,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_note()).collect())
+    }
+
+    async fn get_notes_page(&self, book_id: i32, page: PageArgs) -> Result<KeysetPage<Note>> {
+        let rows = if page.from_end {
+            sqlx::query_as!(
+                SqlNote,
+                "SELECT id, book_id, note, page, reading_id FROM note WHERE book_id=$1 AND ($2::int IS NULL OR id > $2) AND ($3::int IS NULL OR id < $3) ORDER BY id DESC LIMIT $4",
+                book_id,
+                page.after,
+                page.before,
+                page.fetch_limit(),
+            )
+            .fetch_all(&self.db)
+            .await?
+        } else {
+            sqlx::query_as!(
+                SqlNote,
+                "SELECT id, book_id, note, page, reading_id FROM note WHERE book_id=$1 AND ($2::int IS NULL OR id > $2) AND ($3::int IS NULL OR id < $3) ORDER BY id ASC LIMIT $4",
+                book_id,
+                page.after,
+                page.before,
+                page.fetch_limit(),
+            )
+            .fetch_all(&self.db)
+            .await?
+        };
+
+        let notes = rows.into_iter().map(|row| row.into_note()).collect();
+        Ok(KeysetPage::from_rows(notes, page))
+    }
+
+    async fn get_note_by_id(&self, note_id: i32) -> Result<Option<Note>> {
+        let row = sqlx::query_as!(
+            SqlNote,
+            "SELECT id, book_id, note, page, reading_id FROM note WHERE id=$1",
+            note_id,
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(row.map(SqlNote::into_note))
+    }
+
+    async fn find_library_books(&self, keys: &[LibraryBookKey]) -> Result<Vec<Book>> {
+        let providers: Vec<ExternalProvider> = keys.iter().map(|key| key.provider).collect();
+        let external_ids: Vec<String> = keys.iter().map(|key| key.external_id.clone()).collect();
+        let isbn_10s: Vec<String> = keys.iter().filter_map(|key| key.isbn_10.clone()).collect();
+        let isbn_13s: Vec<String> = keys.iter().filter_map(|key| key.isbn_13.clone()).collect();
+        let (titles, authors): (Vec<String>, Vec<String>) = keys
+            .iter()
+            .filter_map(|key| Some((key.title.clone(), key.author.clone()?)))
+            .unzip();
+
+        let rows = sqlx::query_as!(
+            SqlBook,
+            r#"SELECT id, title, author, image_url, year, pages, status AS "status: _", isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS "external_provider: _", (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book
+            WHERE id IN (SELECT book_id FROM book_external_id WHERE (provider, external_id) IN (SELECT * FROM UNNEST($1::provider[], $2::text[])))
+                OR isbn_10 = ANY($3) OR isbn_13 = ANY($4)
+                OR (title, author) IN (SELECT * FROM UNNEST($5::text[], $6::text[]))
+            ORDER BY id"#,
+            providers as _,
+            &external_ids,
+            &isbn_10s,
+            &isbn_13s,
+            &titles,
+            &authors,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_book()).collect())
+    }
+
+    async fn search_library(&self, query: String, limit: usize) -> Result<Vec<LibrarySearchHit>> {
+        // Uses the generated `search` columns and their GIN indexes. Book snippets highlight the
+        // whole "title by author" string, note snippets a fragment around the matched terms.
+        let rows = sqlx::query_as!(
+            SqlSearchHit,
+            r#"WITH q AS (SELECT websearch_to_tsquery('english', $1) AS query),
+            hits AS (
+                SELECT b.id AS book_id, NULL::int AS note_id, ts_rank(b.search, q.query) AS rank,
+                    ts_headline('english', b.title || ' by ' || b.author, q.query, 'StartSel=<b>, StopSel=</b>, HighlightAll=true') AS snippet
+                FROM book b, q WHERE b.search @@ q.query
+                UNION ALL
+                SELECT n.book_id, n.id, ts_rank(n.search, q.query),
+                    ts_headline('english', n.note, q.query, 'StartSel=<b>, StopSel=</b>, MaxWords=20, MinWords=5')
+                FROM note n, q WHERE n.search @@ q.query
+            )
+            SELECT b.id, b.title, b.author, b.image_url, b.year, b.pages, b.status AS "status: _",
+                b.isbn_10, b.isbn_13,
+                (SELECT provider FROM book_external_id WHERE book_id = b.id) AS "external_provider: _",
+                (SELECT external_id FROM book_external_id WHERE book_id = b.id) AS external_id,
+                h.note_id, n.note AS "note?", n.page AS "page?", n.reading_id AS "reading_id?", h.rank::float8 AS "rank!", h.snippet AS "snippet!"
+            FROM hits h JOIN book b ON b.id = h.book_id LEFT JOIN note n ON n.id = h.note_id
+            ORDER BY h.rank DESC, b.id, h.note_id NULLS FIRST
+            LIMIT $2"#,
+            query,
+            limit as i64,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_hit()).collect())
+    }
+
+    async fn add_note(&self, input: AddNoteInput) -> Result<Note> {
+        let row = sqlx::query_as!(
+            SqlNote,
+            "INSERT INTO note(book_id, note, page, reading_id) VALUES ($1, $2, $3, $4) RETURNING id, book_id, note, page, reading_id",
+            input.book_id,
+            input.note,
+            input.page,
+            input.reading_id,
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(row.into_note())
+    }
+
+    async fn update_note(&self, input: UpdateNoteInput) -> Result<Note> {
+        let set_page = !input.page.is_undefined();
+        let set_reading = !input.reading_id.is_undefined();
+        let row = sqlx::query_as!(
+            SqlNote,
+            "UPDATE note SET note=COALESCE($2, note), page=CASE WHEN $3 THEN $4 ELSE page END, reading_id=CASE WHEN $5 THEN $6 ELSE reading_id END WHERE id=$1 RETURNING id, book_id, note, page, reading_id",
+            input.note_id,
+            input.note,
+            set_page,
+            input.page.take(),
+            set_reading,
+            input.reading_id.take(),
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        match row {
+            Some(row) => Ok(row.into_note()),
+            None => Err(anyhow!("No note with ID {} found.", input.note_id)),
+        }
+    }
+
+    async fn delete_note(&self, note_id: i32) -> Result<()> {
+        let result = sqlx::query!("DELETE FROM note WHERE id=$1", note_id)
+            .execute(&self.db)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("No note with ID {} found.", note_id));
+        }
+        Ok(())
+    }
+
+    async fn get_external_links(&self, book_id: Option<i32>) -> Result<Vec<ExternalLink>> {
+        let links = sqlx::query_as!(
+            ExternalLink,
+            r#"SELECT book_id, provider AS "provider: _", external_id, overridden_fields AS "overridden_fields: _" FROM book_external_id WHERE $1::int IS NULL OR book_id=$1 ORDER BY book_id"#,
+            book_id,
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(links)
+    }
+
+    async fn set_overridden_fields(&self, book_id: i32, fields: &[BookField]) -> Result<()> {
+        sqlx::query!(
+            "UPDATE book_external_id SET overridden_fields=$2 WHERE book_id=$1",
+            book_id,
+            fields as _,
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_cached_response(&self, key: &str, now: i64) -> Result<Option<String>> {
+        let response = sqlx::query_scalar!(
+            "SELECT response FROM metadata_cache WHERE key=$1 AND expires_at > $2",
+            key,
+            now,
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(response)
+    }
+
+    async fn put_cached_response(&self, key: &str, response: &str, expires_at: i64) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO metadata_cache (key, response, expires_at) VALUES ($1, $2, $3) ON CONFLICT (key) DO UPDATE SET response=EXCLUDED.response, expires_at=EXCLUDED.expires_at",
+            key,
+            response,
+            expires_at,
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Fetches a book's credits in credit order.
+async fn book_credits(conn: &mut PgConnection, book_id: i32) -> Result<Vec<BookAuthorInput>> {
+    let rows = sqlx::query_as!(
+        SqlBookAuthor,
+        r#"SELECT ba.book_id, ba.author_id, a.name, ba.role AS "role: _" FROM book_author ba JOIN author a ON a.id = ba.author_id WHERE ba.book_id = $1 ORDER BY ba.position"#,
+        book_id,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| row.into_credit()).collect())
+}
+
+/// Replaces a book's credits, creating any authors that don't exist yet.
+async fn set_book_authors(
+    conn: &mut PgConnection,
+    book_id: i32,
+    credits: &[BookAuthorInput],
+) -> Result<()> {
+    sqlx::query!("DELETE FROM book_author WHERE book_id=$1", book_id)
+        .execute(&mut *conn)
+        .await?;
+
+    for (position, credit) in credits.iter().enumerate() {
+        // The no-op update makes `RETURNING` return the ID of an existing author too.
+        let author_id = sqlx::query_scalar!(
+            "INSERT INTO author(name) VALUES ($1) ON CONFLICT (name) DO UPDATE SET name=EXCLUDED.name RETURNING id",
+            credit.name,
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        sqlx::query!(
+            "INSERT INTO book_author(book_id, author_id, role, position) VALUES ($1, $2, $3, $4)",
+            book_id,
+            author_id,
+            credit.role as _,
+            position as i32,
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Adds `fields` to those recorded as overridden on a book's provider link. Does nothing if the
+/// book has no link.
+async fn add_overridden_fields(
+    conn: &mut PgConnection,
+    book_id: i32,
+    fields: &[BookField],
+) -> Result<()> {
+    let Some(mut overridden_fields) = sqlx::query_scalar!(
+        r#"SELECT overridden_fields AS "overridden_fields: Vec<BookField>" FROM book_external_id WHERE book_id=$1 FOR UPDATE"#,
+        book_id,
+    )
+    .fetch_optional(&mut *conn)
+    .await?
+    else {
+        return Ok(());
+    };
+
+    merge_overridden_fields(&mut overridden_fields, fields);
+    sqlx::query!(
+        "UPDATE book_external_id SET overridden_fields=$2 WHERE book_id=$1",
+        book_id,
+        overridden_fields as _,
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Moves a book that `lock_book` locked to `status` as of `at`, as `update_book_status` describes.
+async fn change_status(
+    conn: &mut PgConnection,
+    previous: Book,
+    status: ReadingStatus,
+    at: DateTime<Utc>,
+) -> Result<Book> {
+    // Setting the status a book already has isn't a change, so its history is left alone.
+    if previous.status == status {
+        return Ok(previous);
+    }
+    check_transition(previous.status, status)?;
+    check_status_time(last_status_change(&mut *conn, previous.id).await?, at)?;
+
+    let row = sqlx::query_as!(
+        SqlBook,
+        r#"UPDATE book SET status=$1 WHERE id=$2 RETURNING id, title, author, image_url, year, pages, status AS "status: _", isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS "external_provider: _", (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id"#,
+        status as _,
+        previous.id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    add_status_event(&mut *conn, previous.id, status, at).await?;
+    if let Some(change) = reading_change(previous.status, status) {
+        apply_reading_change(&mut *conn, previous.id, change, at).await?;
+    }
+    Ok(row.into_book())
+}
+
+/// Fetches when a book's status last changed, if it ever has.
+async fn last_status_change(
+    conn: &mut PgConnection,
+    book_id: i32,
+) -> Result<Option<DateTime<Utc>>> {
+    let changed_at = sqlx::query_scalar!(
+        "SELECT changed_at FROM status_event WHERE book_id=$1 ORDER BY changed_at DESC, id DESC LIMIT 1",
+        book_id,
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    Ok(changed_at)
+}
+
+/// Records a change to a book's reading status.
+async fn add_status_event(
+    conn: &mut PgConnection,
+    book_id: i32,
+    status: ReadingStatus,
+    at: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query!(
+        "INSERT INTO status_event(book_id, status, changed_at) VALUES ($1, $2, $3)",
+        book_id,
+        status as _,
+        at,
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Starts, ends or drops one of a book's readings.
+async fn apply_reading_change(
+    conn: &mut PgConnection,
+    book_id: i32,
+    change: ReadingChange,
+    at: DateTime<Utc>,
+) -> Result<()> {
+    match change {
+        ReadingChange::Start => {
+            sqlx::query!(
+                "INSERT INTO reading(book_id, started_at) VALUES ($1, $2)",
+                book_id,
+                at,
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(reading_write_error)?;
+        }
+        ReadingChange::End(outcome) => {
+            let result = sqlx::query!(
+                "UPDATE reading SET finished_at=$2, outcome=$3 WHERE book_id=$1 AND outcome IS NULL",
+                book_id,
+                at,
+                outcome as _,
+            )
+            .execute(&mut *conn)
+            .await?;
+            if result.rows_affected() == 0 {
+                sqlx::query!(
+                    "INSERT INTO reading(book_id, finished_at, outcome) VALUES ($1, $2, $3)",
+                    book_id,
+                    at,
+                    outcome as _,
+                )
+                .execute(&mut *conn)
+                .await?;
+            }
+        }
+        ReadingChange::Discard => {
+            sqlx::query!(
+                "DELETE FROM reading WHERE book_id=$1 AND outcome IS NULL",
+                book_id
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Fetches a book and locks its row until the transaction ends, so that changes that depend on its
+/// status can't race. Returns an error if there's no book with the ID.
+async fn lock_book(conn: &mut PgConnection, book_id: i32) -> Result<Book> {
+    let row = sqlx::query_as!(
+        SqlBook,
+        r#"SELECT id, title, author, image_url, year, pages, status AS "status: _", isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS "external_provider: _", (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book WHERE id=$1 FOR UPDATE"#,
+        book_id,
+    )
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| anyhow!("No book with ID {} found.", book_id))?;
+
+    Ok(row.into_book())
+}
+
+/// Returns an error if there's no book with the ID.
+async fn book_exists(conn: &mut PgConnection, book_id: i32) -> Result<()> {
+    sqlx::query!("SELECT id FROM book WHERE id=$1", book_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| anyhow!("No book with ID {} found.", book_id))?;
+    Ok(())
+}
+
+/// Maps errors from writing a shelf to messages that can be shown to the user.
+fn shelf_write_error(error: sqlx::Error) -> anyhow::Error {
+    match error {
+        sqlx::Error::Database(error) if error.constraint() == Some("shelf_name_key") => {
+            anyhow!("A shelf with this name already exists.")
+        }
+        _ => anyhow!("There was an error connecting to the database."),
+    }
+}
+
+/// Maps the error from starting a reading while one is in progress to a `StatusChangeError`.
+fn reading_write_error(error: sqlx::Error) -> anyhow::Error {
+    match error {
+        sqlx::Error::Database(error) if error.constraint() == Some("reading_in_progress_key") => {
+            StatusChangeError::AlreadyReading.into()
+        }
+        error => error.into(),
+    }
+}
+
+/// Maps an error from inserting or updating a `book` row to a user-facing error.
+fn book_write_error(error: sqlx::Error) -> anyhow::Error {
+    match error {
+        sqlx::Error::Database(error) if error.constraint() == Some("book_title_author_key") => {
+            anyhow!("This book title and author combination has already been added.")
+        }
+        sqlx::Error::Database(error)
+            if matches!(
+                error.constraint(),
+                Some("book_isbn_10_key" | "book_isbn_13_key")
+            ) =>
+        {
+            anyhow!("A book with this ISBN has already been added.")
+        }
+        sqlx::Error::Database(error) if error.constraint() == Some("book_external_id_key") => {
+            anyhow!("This book has already been added from its provider.")
+        }
+        _ => anyhow!("There was an error connecting to the database."),
+    }
+}
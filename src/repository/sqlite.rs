@@ -0,0 +1,1302 @@
+use super::{
+    book_order_by, check_progress_time, check_status_time, check_transition,
+    merge_overridden_fields, reading_change, status_filter, with_primary_author, AuthorCounts,
+    BookStore, BookTag, ExternalLink, KeysetPage, LibraryBookKey, NextInSeries, PageArgs,
+    ReadingChange, SeriesEntry, SqlBook, SqlBookAuthor, SqlNextInSeries, SqlNote, SqlSearchHit,
+    SqlSeriesEntry, StatusChangeError,
+};
+use crate::graphql::{
+    AddBookInput, AddNoteInput, Author, Book, BookAuthor, BookAuthorInput, BookField, BookFilter,
+    BookSort, ExternalProvider, LibrarySearchHit, Note, ProgressEntry, Reading, ReadingStatus,
+    Series, Shelf, SortDirection, StatusEvent, UpdateBookInput, UpdateNoteInput,
+};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, QueryBuilder, Sqlite, SqliteConnection};
+
+/// SQLite implementation of `BookStore`, for running Borges from a single database file.
+///
+/// The SQLite schema lives in `sqlite_migrations/` and mirrors the PostgreSQL one, with the
+/// `status` enum stored as checked `TEXT`. Queries are checked at runtime since the `query!`
+/// macros are bound to the PostgreSQL `DATABASE_URL`.
+pub struct SqliteBookRepository {
+    pub db: Pool<Sqlite>,
+}
+
+impl SqliteBookRepository {
+    pub fn new(conn: Pool<Sqlite>) -> Self {
+        Self { db: conn }
+    }
+}
+
+#[async_trait]
+impl BookStore for SqliteBookRepository {
+    async fn get_book_by_title(&self, title: String) -> Result<Option<Book>> {
+        let row = sqlx::query_as::<_, SqlBook>(
+            "SELECT id, title, author, image_url, year, pages, status, isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS external_provider, (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book WHERE title=?",
+        )
+        .bind(title)
+        .fetch_optional(&self.db)
+        .await
+        .unwrap_or(None);
+
+        Ok(row.map(SqlBook::into_book))
+    }
+
+    async fn get_book_by_id(&self, book_id: i32) -> Result<Option<Book>> {
+        let row = sqlx::query_as::<_, SqlBook>(
+            "SELECT id, title, author, image_url, year, pages, status, isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS external_provider, (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book WHERE id=?",
+        )
+        .bind(book_id)
+        .fetch_optional(&self.db)
+        .await
+        .unwrap_or(None);
+
+        Ok(row.map(SqlBook::into_book))
+    }
+
+    async fn get_book_by_isbn(&self, isbn_13: String) -> Result<Option<Book>> {
+        let row = sqlx::query_as::<_, SqlBook>(
+            "SELECT id, title, author, image_url, year, pages, status, isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS external_provider, (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book WHERE isbn_13=?",
+        )
+        .bind(isbn_13)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(row.map(SqlBook::into_book))
+    }
+
+    async fn get_books(
+        &self,
+        filter: BookFilter,
+        sort: BookSort,
+        direction: SortDirection,
+    ) -> Result<Option<Vec<Book>>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT id, title, author, image_url, year, pages, status, isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS external_provider, (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book WHERE TRUE",
+        );
+        if let Some(author) = filter.author {
+            query
+                .push(" AND instr(lower(author), lower(")
+                .push_bind(author)
+                .push(")) > 0");
+        }
+        if let Some(title) = filter.title {
+            query
+                .push(" AND instr(lower(title), lower(")
+                .push_bind(title)
+                .push(")) > 0");
+        }
+        if let Some(min_year) = filter.min_year {
+            query.push(" AND year >= ").push_bind(min_year);
+        }
+        if let Some(max_year) = filter.max_year {
+            query.push(" AND year <= ").push_bind(max_year);
+        }
+        if let Some(min_pages) = filter.min_pages {
+            query.push(" AND pages >= ").push_bind(min_pages);
+        }
+        if let Some(max_pages) = filter.max_pages {
+            query.push(" AND pages <= ").push_bind(max_pages);
+        }
+        // SQLite has no array binds, so expand the statuses into an `IN (...)` list.
+        query.push(" AND status IN (");
+        let mut separated = query.separated(", ");
+        for status in filter.statuses.unwrap_or_else(|| status_filter(None)) {
+            separated.push_bind(status);
+        }
+        separated.push_unseparated(")");
+        if let Some(mut tags) = filter.tags.filter(|tags| !tags.is_empty()) {
+            tags.sort();
+            tags.dedup();
+            query.push(" AND id IN (SELECT book_id FROM book_tag WHERE tag IN (");
+            let count = tags.len() as i64;
+            let mut separated = query.separated(", ");
+            for tag in tags {
+                separated.push_bind(tag);
+            }
+            separated.push_unseparated(") GROUP BY book_id HAVING COUNT(*) = ");
+            query.push_bind(count).push(")");
+        }
+        if let Some(shelf_id) = filter.shelf {
+            query
+                .push(" AND id IN (SELECT book_id FROM shelf_book WHERE shelf_id = ")
+                .push_bind(shelf_id)
+                .push(")");
+        }
+        query.push(book_order_by(sort, direction));
+
+        let rows = query
+            .build_query_as::<SqlBook>()
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(Some(rows.into_iter().map(|row| row.into_book()).collect()))
+    }
+
+    async fn get_books_page(
+        &self,
+        status: Option<ReadingStatus>,
+        page: PageArgs,
+    ) -> Result<KeysetPage<Book>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT id, title, author, image_url, year, pages, status, isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS external_provider, (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book WHERE status IN (",
+        );
+        let mut separated = query.separated(", ");
+        for status in status_filter(status) {
+            separated.push_bind(status);
+        }
+        separated.push_unseparated(")");
+        push_keyset_clauses(&mut query, page);
+
+        let rows = query
+            .build_query_as::<SqlBook>()
+            .fetch_all(&self.db)
+            .await?;
+
+        let books = rows.into_iter().map(|row| row.into_book()).collect();
+        Ok(KeysetPage::from_rows(books, page))
+    }
+
+    async fn add_book(&self, input: AddBookInput) -> Result<Book> {
+        let credits = input.credits();
+        let status = input.status.unwrap_or_default();
+        let mut tx = self.db.begin().await?;
+
+        let book_id: i32 = sqlx::query_scalar(
+            "INSERT INTO book(title, author, image_url, year, pages, status, isbn_10, isbn_13) VALUES (?, ?, ?, ?, ?, ?, ?, ?) RETURNING id",
+        )
+        .bind(input.title)
+        .bind(input.author)
+        .bind(input.image_url)
+        .bind(input.year)
+        .bind(input.pages)
+        .bind(status)
+        .bind(input.isbn_10)
+        .bind(input.isbn_13)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(book_write_error)?;
+
+        if let (Some(provider), Some(external_id)) = (input.external_provider, input.external_id) {
+            sqlx::query(
+                "INSERT INTO book_external_id(book_id, provider, external_id, overridden_fields) VALUES (?, ?, ?, ?)",
+            )
+            .bind(book_id)
+            .bind(provider)
+            .bind(external_id)
+            .bind(serde_json::to_string(&input.overridden_fields)?)
+            .execute(&mut *tx)
+            .await
+            .map_err(book_write_error)?;
+        }
+        set_book_authors(&mut tx, book_id, &credits).await?;
+        add_status_event(&mut tx, book_id, status, Utc::now()).await?;
+
+        let row = sqlx::query_as::<_, SqlBook>(
+            "SELECT id, title, author, image_url, year, pages, status, isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS external_provider, (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book WHERE id=?",
+        )
+        .bind(book_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(row.into_book())
+    }
+
+    async fn update_book(&self, input: UpdateBookInput) -> Result<Book> {
+        let mut tx = self.db.begin().await?;
+
+        let set_image_url = !input.image_url.is_undefined();
+        let row = sqlx::query_as::<_, SqlBook>(
+            "UPDATE book SET title=COALESCE(?2, title), author=COALESCE(?3, author), image_url=CASE WHEN ?4 THEN ?5 ELSE image_url END, year=COALESCE(?6, year), pages=COALESCE(?7, pages) WHERE id=?1 RETURNING id, title, author, image_url, year, pages, status, isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS external_provider, (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id",
+        )
+        .bind(input.book_id)
+        .bind(&input.title)
+        .bind(&input.author)
+        .bind(set_image_url)
+        .bind(input.image_url.take())
+        .bind(input.year)
+        .bind(input.pages)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(book_write_error)?
+        .ok_or_else(|| anyhow!("No book with ID {} found.", input.book_id))?;
+
+        // Setting just `author` replaces the primary credit and keeps the others.
+        let credits = match (input.authors, &input.author) {
+            (Some(credits), _) => Some(credits),
+            (None, Some(author)) => Some(with_primary_author(
+                book_credits(&mut tx, input.book_id).await?,
+                author,
+            )),
+            (None, None) => None,
+        };
+        if let Some(credits) = &credits {
+            set_book_authors(&mut tx, input.book_id, credits).await?;
+        }
+        if !input.overridden_fields.is_empty() {
+            add_overridden_fields(&mut tx, input.book_id, &input.overridden_fields).await?;
+        }
+        tx.commit().await?;
+
+        Ok(row.into_book())
+    }
+
+    async fn delete_book(&self, book_id: i32) -> Result<()> {
+        // Notes are removed by the `ON DELETE CASCADE` on `fk_book_id`.
+        let result = sqlx::query("DELETE FROM book WHERE id=?")
+            .bind(book_id)
+            .execute(&self.db)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("No book with ID {} found.", book_id));
+        }
+        Ok(())
+    }
+
+    async fn update_book_status(
+        &self,
+        book_id: i32,
+        status: ReadingStatus,
+        at: DateTime<Utc>,
+    ) -> Result<Book> {
+        let mut tx = self.db.begin().await?;
+
+        let book = lock_book(&mut tx, book_id).await?;
+        let book = change_status(&mut tx, book, status, at).await?;
+        tx.commit().await?;
+
+        Ok(book)
+    }
+
+    async fn start_rereading(&self, book_id: i32, at: DateTime<Utc>) -> Result<Book> {
+        let mut tx = self.db.begin().await?;
+
+        let book = lock_book(&mut tx, book_id).await?;
+        if book.status != ReadingStatus::Read {
+            return Err(StatusChangeError::NotRead.into());
+        }
+        let book = change_status(&mut tx, book, ReadingStatus::Reading, at).await?;
+        tx.commit().await?;
+
+        Ok(book)
+    }
+
+    async fn get_readings(&self, book_ids: &[i32]) -> Result<Vec<Reading>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT id, book_id, started_at, finished_at, outcome FROM reading WHERE book_id IN (",
+        );
+        let mut separated = query.separated(", ");
+        for book_id in book_ids {
+            separated.push_bind(book_id);
+        }
+        separated.push_unseparated(") ORDER BY book_id, id");
+
+        let rows = query
+            .build_query_as::<Reading>()
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(rows)
+    }
+
+    async fn get_reading_by_id(&self, reading_id: i32) -> Result<Option<Reading>> {
+        let row = sqlx::query_as::<_, Reading>(
+            "SELECT id, book_id, started_at, finished_at, outcome FROM reading WHERE id=?",
+        )
+        .bind(reading_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn log_progress(
+        &self,
+        book_id: i32,
+        page: i32,
+        at: DateTime<Utc>,
+    ) -> Result<(Book, ProgressEntry)> {
+        let mut tx = self.db.begin().await?;
+
+        let book = lock_book(&mut tx, book_id).await?;
+        let reading_id: Option<i32> =
+            sqlx::query_scalar("SELECT id FROM reading WHERE book_id=? AND outcome IS NULL")
+                .bind(book_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let Some(reading_id) = reading_id.filter(|_| book.status == ReadingStatus::Reading) else {
+            return Err(StatusChangeError::NotReading.into());
+        };
+        check_progress_time(last_status_change(&mut tx, book_id).await?, at)?;
+
+        let entry = sqlx::query_as::<_, ProgressEntry>(
+            "INSERT INTO reading_progress(reading_id, page, logged_at) VALUES (?, ?, ?) RETURNING id, reading_id, page, logged_at AS at",
+        )
+        .bind(reading_id)
+        .bind(page)
+        .bind(at)
+        .fetch_one(&mut *tx)
+        .await?;
+        // Reaching the last page finishes the book.
+        let book = if page == book.pages {
+            change_status(&mut tx, book, ReadingStatus::Read, at).await?
+        } else {
+            book
+        };
+        tx.commit().await?;
+
+        Ok((book, entry))
+    }
+
+    async fn get_reading_progress(&self, reading_ids: &[i32]) -> Result<Vec<ProgressEntry>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT id, reading_id, page, logged_at AS at FROM reading_progress WHERE reading_id IN (",
+        );
+        let mut separated = query.separated(", ");
+        for reading_id in reading_ids {
+            separated.push_bind(reading_id);
+        }
+        separated.push_unseparated(") ORDER BY reading_id, logged_at, id");
+
+        let rows = query
+            .build_query_as::<ProgressEntry>()
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(rows)
+    }
+
+    async fn get_status_history(&self, book_ids: &[i32]) -> Result<Vec<StatusEvent>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT book_id, status, changed_at AS at FROM status_event WHERE book_id IN (",
+        );
+        let mut separated = query.separated(", ");
+        for book_id in book_ids {
+            separated.push_bind(book_id);
+        }
+        separated.push_unseparated(") ORDER BY book_id, changed_at, id");
+
+        let rows = query
+            .build_query_as::<StatusEvent>()
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(rows)
+    }
+
+    async fn get_notes_by_book(&self, book_id: i32) -> Result<Option<Vec<Note>>> {
+        let rows = sqlx::query_as::<_, SqlNote>(
+            "SELECT id, book_id, note, page, reading_id FROM note WHERE book_id=?",
+        )
+        .bind(book_id)
+        .fetch_all(&self.db)
+        .await
+        .unwrap_or_default();
+
+        Ok(Some(rows.into_iter().map(|row| row.into_note()).collect()))
+    }
+
+    async fn get_book_authors(&self, book_ids: &[i32]) -> Result<Vec<BookAuthor>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT ba.book_id, ba.author_id, a.name, ba.role FROM book_author ba JOIN author a ON a.id = ba.author_id WHERE ba.book_id IN (",
+        );
+        let mut separated = query.separated(", ");
+        for book_id in book_ids {
+            separated.push_bind(book_id);
+        }
+        separated.push_unseparated(") ORDER BY ba.book_id, ba.position");
+
+        let rows = query
+            .build_query_as::<SqlBookAuthor>()
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_book_author()).collect())
+    }
+
+    async fn get_books_by_author(&self, author_id: i32) -> Result<Vec<Book>> {
+        let rows = sqlx::query_as::<_, SqlBook>(
+            "SELECT id, title, author, image_url, year, pages, status, isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS external_provider, (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book WHERE id IN (SELECT book_id FROM book_author WHERE author_id=?) ORDER BY id",
+        )
+        .bind(author_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_book()).collect())
+    }
+
+    async fn get_authors(&self) -> Result<Vec<Author>> {
+        let authors = sqlx::query_as::<_, Author>("SELECT id, name FROM author ORDER BY name")
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(authors)
+    }
+
+    async fn get_author_by_id(&self, author_id: i32) -> Result<Option<Author>> {
+        let author = sqlx::query_as::<_, Author>("SELECT id, name FROM author WHERE id=?")
+            .bind(author_id)
+            .fetch_optional(&self.db)
+            .await?;
+
+        Ok(author)
+    }
+
+    async fn get_author_counts(&self, author_ids: &[i32]) -> Result<Vec<AuthorCounts>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT ba.author_id, COUNT(DISTINCT ba.book_id) AS books, COUNT(DISTINCT CASE WHEN b.status = 'read' THEN ba.book_id END) AS read FROM book_author ba JOIN book b ON b.id = ba.book_id WHERE ba.author_id IN (",
+        );
+        let mut separated = query.separated(", ");
+        for author_id in author_ids {
+            separated.push_bind(author_id);
+        }
+        separated.push_unseparated(") GROUP BY ba.author_id");
+
+        let counts = query
+            .build_query_as::<AuthorCounts>()
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(counts)
+    }
+
+    async fn merge_authors(&self, source_ids: &[i32], target_id: i32) -> Result<Author> {
+        let mut tx = self.db.begin().await?;
+
+        let target = sqlx::query_as::<_, Author>("SELECT id, name FROM author WHERE id=?")
+            .bind(target_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| anyhow!("No author with ID {} found.", target_id))?;
+
+        for source_id in source_ids {
+            let source_name: String = sqlx::query_scalar("SELECT name FROM author WHERE id=?")
+                .bind(source_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or_else(|| anyhow!("No author with ID {} found.", source_id))?;
+
+            sqlx::query(
+                "DELETE FROM book_author AS s WHERE author_id=?1 AND EXISTS (SELECT 1 FROM book_author t WHERE t.book_id = s.book_id AND t.role = s.role AND t.author_id = ?2)",
+            )
+            .bind(source_id)
+            .bind(target_id)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query("UPDATE book_author SET author_id=?2 WHERE author_id=?1")
+                .bind(source_id)
+                .bind(target_id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("UPDATE book SET author=?2 WHERE author=?1")
+                .bind(&source_name)
+                .bind(&target.name)
+                .execute(&mut *tx)
+                .await
+                .map_err(book_write_error)?;
+            sqlx::query("DELETE FROM author WHERE id=?")
+                .bind(source_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(target)
+    }
+
+    async fn get_book_tags(&self, book_ids: &[i32]) -> Result<Vec<BookTag>> {
+        let mut query =
+            QueryBuilder::<Sqlite>::new("SELECT book_id, tag FROM book_tag WHERE book_id IN (");
+        let mut separated = query.separated(", ");
+        for book_id in book_ids {
+            separated.push_bind(book_id);
+        }
+        separated.push_unseparated(") ORDER BY book_id, tag");
+
+        let rows = query
+            .build_query_as::<BookTag>()
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(rows)
+    }
+
+    async fn get_tags(&self) -> Result<Vec<String>> {
+        let tags = sqlx::query_scalar("SELECT DISTINCT tag FROM book_tag ORDER BY tag")
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(tags)
+    }
+
+    async fn add_book_tags(&self, book_id: i32, tags: &[String]) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        book_exists(&mut tx, book_id).await?;
+        for tag in tags {
+            sqlx::query("INSERT INTO book_tag(book_id, tag) VALUES (?, ?) ON CONFLICT DO NOTHING")
+                .bind(book_id)
+                .bind(tag)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn remove_book_tags(&self, book_id: i32, tags: &[String]) -> Result<()> {
+        let mut query = QueryBuilder::<Sqlite>::new("DELETE FROM book_tag WHERE book_id=");
+        query.push_bind(book_id).push(" AND tag IN (");
+        let mut separated = query.separated(", ");
+        for tag in tags {
+            separated.push_bind(tag);
+        }
+        separated.push_unseparated(")");
+
+        query.build().execute(&self.db).await?;
+
+        Ok(())
+    }
+
+    async fn get_shelves(&self) -> Result<Vec<Shelf>> {
+        let shelves = sqlx::query_as::<_, Shelf>("SELECT id, name FROM shelf ORDER BY name")
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(shelves)
+    }
+
+    async fn get_shelf_by_id(&self, shelf_id: i32) -> Result<Option<Shelf>> {
+        let shelf = sqlx::query_as::<_, Shelf>("SELECT id, name FROM shelf WHERE id=?")
+            .bind(shelf_id)
+            .fetch_optional(&self.db)
+            .await?;
+
+        Ok(shelf)
+    }
+
+    async fn create_shelf(&self, name: &str) -> Result<Shelf> {
+        let shelf =
+            sqlx::query_as::<_, Shelf>("INSERT INTO shelf(name) VALUES (?) RETURNING id, name")
+                .bind(name)
+                .fetch_one(&self.db)
+                .await
+                .map_err(shelf_write_error)?;
+
+        Ok(shelf)
+    }
+
+    async fn rename_shelf(&self, shelf_id: i32, name: &str) -> Result<Shelf> {
+        sqlx::query_as::<_, Shelf>("UPDATE shelf SET name=?2 WHERE id=?1 RETURNING id, name")
+            .bind(shelf_id)
+            .bind(name)
+            .fetch_optional(&self.db)
+            .await
+            .map_err(shelf_write_error)?
+            .ok_or_else(|| anyhow!("No shelf with ID {} found.", shelf_id))
+    }
+
+    async fn delete_shelf(&self, shelf_id: i32) -> Result<()> {
+        // The shelf's books are taken off it by the `ON DELETE CASCADE` on `shelf_book`.
+        let result = sqlx::query("DELETE FROM shelf WHERE id=?")
+            .bind(shelf_id)
+            .execute(&self.db)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("No shelf with ID {} found.", shelf_id));
+        }
+        Ok(())
+    }
+
+    async fn get_shelf_books(&self, shelf_id: i32) -> Result<Vec<Book>> {
+        let rows = sqlx::query_as::<_, SqlBook>(
+            "SELECT id, title, author, image_url, year, pages, status, isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS external_provider, (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book JOIN shelf_book ON shelf_book.book_id = book.id WHERE shelf_book.shelf_id=? ORDER BY shelf_book.position",
+        )
+        .bind(shelf_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_book()).collect())
+    }
+
+    async fn add_book_to_shelf(
+        &self,
+        shelf_id: i32,
+        book_id: i32,
+        position: Option<usize>,
+    ) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query("SELECT id FROM shelf WHERE id=?")
+            .bind(shelf_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| anyhow!("No shelf with ID {} found.", shelf_id))?;
+        book_exists(&mut tx, book_id).await?;
+
+        // The shelf is rewritten in its new order, which keeps the positions contiguous.
+        let mut book_ids: Vec<i32> = sqlx::query_scalar(
+            "SELECT book_id FROM shelf_book WHERE shelf_id=? AND book_id<>? ORDER BY position",
+        )
+        .bind(shelf_id)
+        .bind(book_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        let position = position.unwrap_or(book_ids.len()).min(book_ids.len());
+        book_ids.insert(position, book_id);
+
+        sqlx::query("DELETE FROM shelf_book WHERE shelf_id=?")
+            .bind(shelf_id)
+            .execute(&mut *tx)
+            .await?;
+        for (position, book_id) in book_ids.into_iter().enumerate() {
+            sqlx::query("INSERT INTO shelf_book(shelf_id, book_id, position) VALUES (?, ?, ?)")
+                .bind(shelf_id)
+                .bind(book_id)
+                .bind(position as i32)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn remove_book_from_shelf(&self, shelf_id: i32, book_id: i32) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        let position: i32 = sqlx::query_scalar(
+            "DELETE FROM shelf_book WHERE shelf_id=? AND book_id=? RETURNING position",
+        )
+        .bind(shelf_id)
+        .bind(book_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| anyhow!("No book with ID {} found on that shelf.", book_id))?;
+        sqlx::query(
+            "UPDATE shelf_book SET position = position - 1 WHERE shelf_id=? AND position > ?",
+        )
+        .bind(shelf_id)
+        .bind(position)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_series_by_id(&self, series_id: i32) -> Result<Option<Series>> {
+        let series = sqlx::query_as::<_, Series>("SELECT id, name FROM series WHERE id=?")
+            .bind(series_id)
+            .fetch_optional(&self.db)
+            .await?;
+
+        Ok(series)
+    }
+
+    async fn create_series(&self, name: &str) -> Result<Series> {
+        let series =
+            sqlx::query_as::<_, Series>("INSERT INTO series(name) VALUES (?) RETURNING id, name")
+                .bind(name)
+                .fetch_one(&self.db)
+                .await
+                .map_err(|error| match error {
+                    sqlx::Error::Database(error) if error.is_unique_violation() => {
+                        anyhow!("A series with this name already exists.")
+                    }
+                    _ => anyhow!("There was an error connecting to the database."),
+                })?;
+
+        Ok(series)
+    }
+
+    async fn get_series_entries(&self, book_ids: &[i32]) -> Result<Vec<SeriesEntry>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT bs.book_id, bs.series_id, s.name, bs.position FROM book_series bs JOIN series s ON s.id = bs.series_id WHERE bs.book_id IN (",
+        );
+        let mut separated = query.separated(", ");
+        for book_id in book_ids {
+            separated.push_bind(book_id);
+        }
+        separated.push_unseparated(")");
+
+        let rows = query
+            .build_query_as::<SqlSeriesEntry>()
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_entry()).collect())
+    }
+
+    async fn get_series_books(&self, series_id: i32) -> Result<Vec<Book>> {
+        let rows = sqlx::query_as::<_, SqlBook>(
+            "SELECT id, title, author, image_url, year, pages, status, isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS external_provider, (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book JOIN book_series ON book_series.book_id = book.id WHERE book_series.series_id=? ORDER BY book_series.position, book.id",
+        )
+        .bind(series_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_book()).collect())
+    }
+
+    async fn set_book_series(&self, book_id: i32, series_id: i32, position: f64) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        book_exists(&mut tx, book_id).await?;
+        sqlx::query("SELECT id FROM series WHERE id=?")
+            .bind(series_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| anyhow!("No series with ID {} found.", series_id))?;
+        sqlx::query(
+            "INSERT INTO book_series(book_id, series_id, position) VALUES (?, ?, ?) ON CONFLICT (book_id) DO UPDATE SET series_id=excluded.series_id, position=excluded.position",
+        )
+        .bind(book_id)
+        .bind(series_id)
+        .bind(position)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn remove_book_from_series(&self, book_id: i32) -> Result<()> {
+        let result = sqlx::query("DELETE FROM book_series WHERE book_id=?")
+            .bind(book_id)
+            .execute(&self.db)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("The book with ID {} isn't in a series.", book_id));
+        }
+        Ok(())
+    }
+
+    async fn get_next_in_series(&self, book_ids: &[i32]) -> Result<Vec<NextInSeries>> {
+        // SQLite has no `DISTINCT ON`, so each book's candidates are numbered to keep the first.
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT previous_id, id, title, author, image_url, year, pages, status, isbn_10, isbn_13, external_provider, external_id FROM (SELECT current.book_id AS previous_id, book.id, title, author, image_url, year, pages, book.status, isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS external_provider, (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id, ROW_NUMBER() OVER (PARTITION BY current.book_id ORDER BY next.position, next.book_id) AS candidate FROM book JOIN book_series next ON next.book_id = book.id JOIN book_series current ON current.series_id = next.series_id WHERE (next.position, next.book_id) > (current.position, current.book_id) AND book.status = ",
+        );
+        query.push_bind(ReadingStatus::Unread);
+        query.push(" AND current.book_id IN (");
+        let mut separated = query.separated(", ");
+        for book_id in book_ids {
+            separated.push_bind(book_id);
+        }
+        separated.push_unseparated(")) WHERE candidate = 1");
+
+        let rows = query
+            .build_query_as::<SqlNextInSeries>()
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_next()).collect())
+    }
+
+    async fn get_notes_by_books(&self, book_ids: &[i32]) -> Result<Vec<Note>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT id, book_id, note, page, reading_id FROM note WHERE book_id IN (",
+        );
+        let mut separated = query.separated(", ");
+        for book_id in book_ids {
+            separated.push_bind(book_id);
+        }
+        separated.push_unseparated(") ORDER BY id");
+
+        let rows = query
+            .build_query_as::<SqlNote>()
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_note()).collect())
+    }
+
+    async fn get_notes_page(&self, book_id: i32, page: PageArgs) -> Result<KeysetPage<Note>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT id, book_id, note, page, reading_id FROM note WHERE book_id=",
+        );
+        query.push_bind(book_id);
+        push_keyset_clauses(&mut query, page);
+
+        let rows = query
+            .build_query_as::<SqlNote>()
+            .fetch_all(&self.db)
+            .await?;
+
+        let notes = rows.into_iter().map(|row| row.into_note()).collect();
+        Ok(KeysetPage::from_rows(notes, page))
+    }
+
+    async fn get_note_by_id(&self, note_id: i32) -> Result<Option<Note>> {
+        let row = sqlx::query_as::<_, SqlNote>(
+            "SELECT id, book_id, note, page, reading_id FROM note WHERE id=?",
+        )
+        .bind(note_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(row.map(SqlNote::into_note))
+    }
+
+    async fn find_library_books(&self, keys: &[LibraryBookKey]) -> Result<Vec<Book>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT id, title, author, image_url, year, pages, status, isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS external_provider, (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id FROM book WHERE FALSE",
+        );
+        for key in keys {
+            query
+                .push(" OR id IN (SELECT book_id FROM book_external_id WHERE provider = ")
+                .push_bind(key.provider)
+                .push(" AND external_id = ")
+                .push_bind(key.external_id.clone())
+                .push(")");
+            if let Some(isbn_10) = &key.isbn_10 {
+                query.push(" OR isbn_10 = ").push_bind(isbn_10.clone());
+            }
+            if let Some(isbn_13) = &key.isbn_13 {
+                query.push(" OR isbn_13 = ").push_bind(isbn_13.clone());
+            }
+            if let Some(author) = &key.author {
+                query
+                    .push(" OR (title = ")
+                    .push_bind(key.title.clone())
+                    .push(" AND author = ")
+                    .push_bind(author.clone())
+                    .push(")");
+            }
+        }
+        query.push(" ORDER BY id");
+
+        let rows = query
+            .build_query_as::<SqlBook>()
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_book()).collect())
+    }
+
+    async fn search_library(&self, query: String, limit: usize) -> Result<Vec<LibrarySearchHit>> {
+        let Some(query) = fts_query(&query) else {
+            return Ok(Vec::new());
+        };
+
+        // Uses the FTS5 `book_search` and `note_search` tables. `bm25` scores are lower for better
+        // matches, so they're negated into ranks, with title matches weighted over author matches.
+        let rows = sqlx::query_as::<_, SqlSearchHit>(
+            "SELECT b.id, b.title, b.author, b.image_url, b.year, b.pages, b.status, b.isbn_10, b.isbn_13, (SELECT provider FROM book_external_id WHERE book_id = b.id) AS external_provider, (SELECT external_id FROM book_external_id WHERE book_id = b.id) AS external_id, h.note_id, n.note, n.page, n.reading_id, h.rank, h.snippet
+            FROM (
+                SELECT book_search.rowid AS book_id, NULL AS note_id, -bm25(book_search, 10.0, 5.0) AS rank,
+                    highlight(book_search, 0, '<b>', '</b>') || ' by ' || highlight(book_search, 1, '<b>', '</b>') AS snippet
+                FROM book_search WHERE book_search MATCH ?1
+                UNION ALL
+                SELECT note.book_id, note_search.rowid, -bm25(note_search),
+                    snippet(note_search, 0, '<b>', '</b>', '...', 20)
+                FROM note_search JOIN note ON note.id = note_search.rowid WHERE note_search MATCH ?1
+            ) h JOIN book b ON b.id = h.book_id LEFT JOIN note n ON n.id = h.note_id
+            ORDER BY h.rank DESC, b.id, h.note_id NULLS FIRST
+            LIMIT ?2",
+        )
+        .bind(query)
+        .bind(limit as i64)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.into_hit()).collect())
+    }
+
+    async fn add_note(&self, input: AddNoteInput) -> Result<Note> {
+        let row = sqlx::query_as::<_, SqlNote>(
+            "INSERT INTO note(book_id, note, page, reading_id) VALUES (?, ?, ?, ?) RETURNING id, book_id, note, page, reading_id",
+        )
+        .bind(input.book_id)
+        .bind(input.note)
+        .bind(input.page)
+        .bind(input.reading_id)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(row.into_note())
+    }
+
+    async fn update_note(&self, input: UpdateNoteInput) -> Result<Note> {
+        let set_page = !input.page.is_undefined();
+        let set_reading = !input.reading_id.is_undefined();
+        let row = sqlx::query_as::<_, SqlNote>(
+            "UPDATE note SET note=COALESCE(?2, note), page=CASE WHEN ?3 THEN ?4 ELSE page END, reading_id=CASE WHEN ?5 THEN ?6 ELSE reading_id END WHERE id=?1 RETURNING id, book_id, note, page, reading_id",
+        )
+        .bind(input.note_id)
+        .bind(input.note)
+        .bind(set_page)
+        .bind(input.page.take())
+        .bind(set_reading)
+        .bind(input.reading_id.take())
+        .fetch_optional(&self.db)
+        .await?;
+
+        match row {
+            Some(row) => Ok(row.into_note()),
+            None => Err(anyhow!("No note with ID {} found.", input.note_id)),
+        }
+    }
+
+    async fn delete_note(&self, note_id: i32) -> Result<()> {
+        let result = sqlx::query("DELETE FROM note WHERE id=?")
+            .bind(note_id)
+            .execute(&self.db)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("No note with ID {} found.", note_id));
+        }
+        Ok(())
+    }
+
+    async fn get_external_links(&self, book_id: Option<i32>) -> Result<Vec<ExternalLink>> {
+        let rows = sqlx::query_as::<_, SqlExternalLink>(
+            "SELECT book_id, provider, external_id, overridden_fields FROM book_external_id WHERE ?1 IS NULL OR book_id=?1 ORDER BY book_id",
+        )
+        .bind(book_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        rows.into_iter().map(SqlExternalLink::into_link).collect()
+    }
+
+    async fn set_overridden_fields(&self, book_id: i32, fields: &[BookField]) -> Result<()> {
+        sqlx::query("UPDATE book_external_id SET overridden_fields=? WHERE book_id=?")
+            .bind(serde_json::to_string(fields)?)
+            .bind(book_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_cached_response(&self, key: &str, now: i64) -> Result<Option<String>> {
+        let response = sqlx::query_scalar(
+            "SELECT response FROM metadata_cache WHERE key=? AND expires_at > ?",
+        )
+        .bind(key)
+        .bind(now)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(response)
+    }
+
+    async fn put_cached_response(&self, key: &str, response: &str, expires_at: i64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO metadata_cache (key, response, expires_at) VALUES (?, ?, ?) ON CONFLICT (key) DO UPDATE SET response=excluded.response, expires_at=excluded.expires_at",
+        )
+        .bind(key)
+        .bind(response)
+        .bind(expires_at)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Fetches a book's credits in credit order.
+async fn book_credits(conn: &mut SqliteConnection, book_id: i32) -> Result<Vec<BookAuthorInput>> {
+    let rows = sqlx::query_as::<_, SqlBookAuthor>(
+        "SELECT ba.book_id, ba.author_id, a.name, ba.role FROM book_author ba JOIN author a ON a.id = ba.author_id WHERE ba.book_id = ? ORDER BY ba.position",
+    )
+    .bind(book_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| row.into_credit()).collect())
+}
+
+/// Replaces a book's credits, creating any authors that don't exist yet.
+async fn set_book_authors(
+    conn: &mut SqliteConnection,
+    book_id: i32,
+    credits: &[BookAuthorInput],
+) -> Result<()> {
+    sqlx::query("DELETE FROM book_author WHERE book_id=?")
+        .bind(book_id)
+        .execute(&mut *conn)
+        .await?;
+
+    for (position, credit) in credits.iter().enumerate() {
+        // The no-op update makes `RETURNING` return the ID of an existing author too.
+        let author_id: i32 = sqlx::query_scalar(
+            "INSERT INTO author(name) VALUES (?) ON CONFLICT (name) DO UPDATE SET name=excluded.name RETURNING id",
+        )
+        .bind(&credit.name)
+        .fetch_one(&mut *conn)
+        .await?;
+        sqlx::query(
+            "INSERT INTO book_author(book_id, author_id, role, position) VALUES (?, ?, ?, ?)",
+        )
+        .bind(book_id)
+        .bind(author_id)
+        .bind(credit.role)
+        .bind(position as i32)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// A `book_external_id` row, whose overridden fields are stored as a JSON array.
+#[derive(sqlx::FromRow)]
+struct SqlExternalLink {
+    book_id: i32,
+    provider: ExternalProvider,
+    external_id: String,
+    overridden_fields: String,
+}
+
+impl SqlExternalLink {
+    fn into_link(self) -> Result<ExternalLink> {
+        Ok(ExternalLink {
+            book_id: self.book_id,
+            provider: self.provider,
+            external_id: self.external_id,
+            overridden_fields: serde_json::from_str(&self.overridden_fields)?,
+        })
+    }
+}
+
+/// Converts free text into an FTS5 query matching every word, quoting the words so that FTS5
+/// syntax characters in the input are searched for literally. Returns `None` if there are no
+/// words to search for.
+fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+/// Appends the ID bounds, ordering and limit for `page` to a query that already has a `WHERE`
+/// clause.
+fn push_keyset_clauses(query: &mut QueryBuilder<'_, Sqlite>, page: PageArgs) {
+    if let Some(after) = page.after {
+        query.push(" AND id > ").push_bind(after);
+    }
+    if let Some(before) = page.before {
+        query.push(" AND id < ").push_bind(before);
+    }
+    query.push(if page.from_end {
+        " ORDER BY id DESC LIMIT "
+    } else {
+        " ORDER BY id ASC LIMIT "
+    });
+    query.push_bind(page.fetch_limit());
+}
+
+/// Adds `fields` to those recorded as overridden on a book's provider link. Does nothing if the
+/// book has no link.
+async fn add_overridden_fields(
+    conn: &mut SqliteConnection,
+    book_id: i32,
+    fields: &[BookField],
+) -> Result<()> {
+    let Some(overridden_fields) = sqlx::query_scalar::<_, String>(
+        "SELECT overridden_fields FROM book_external_id WHERE book_id=?",
+    )
+    .bind(book_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let mut overridden_fields: Vec<BookField> = serde_json::from_str(&overridden_fields)?;
+    merge_overridden_fields(&mut overridden_fields, fields);
+    sqlx::query("UPDATE book_external_id SET overridden_fields=? WHERE book_id=?")
+        .bind(serde_json::to_string(&overridden_fields)?)
+        .bind(book_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Fetches a book, first taking the database's write lock with a no-op update since SQLite has no
+/// `FOR UPDATE`, so that changes that depend on its status can't race. Returns an error if there's
+/// no book with the ID.
+async fn lock_book(conn: &mut SqliteConnection, book_id: i32) -> Result<Book> {
+    let row = sqlx::query_as::<_, SqlBook>(
+        "UPDATE book SET id=id WHERE id=? RETURNING id, title, author, image_url, year, pages, status, isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS external_provider, (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id",
+    )
+    .bind(book_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| anyhow!("No book with ID {} found.", book_id))?;
+
+    Ok(row.into_book())
+}
+
+/// Moves a book that `lock_book` locked to `status` as of `at`, as `update_book_status` describes.
+async fn change_status(
+    conn: &mut SqliteConnection,
+    previous: Book,
+    status: ReadingStatus,
+    at: DateTime<Utc>,
+) -> Result<Book> {
+    // Setting the status a book already has isn't a change, so its history is left alone.
+    if previous.status == status {
+        return Ok(previous);
+    }
+    check_transition(previous.status, status)?;
+    check_status_time(last_status_change(&mut *conn, previous.id).await?, at)?;
+
+    let row = sqlx::query_as::<_, SqlBook>(
+        "UPDATE book SET status=? WHERE id=? RETURNING id, title, author, image_url, year, pages, status, isbn_10, isbn_13, (SELECT provider FROM book_external_id WHERE book_id = book.id) AS external_provider, (SELECT external_id FROM book_external_id WHERE book_id = book.id) AS external_id",
+    )
+    .bind(status)
+    .bind(previous.id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    add_status_event(&mut *conn, previous.id, status, at).await?;
+    if let Some(change) = reading_change(previous.status, status) {
+        apply_reading_change(&mut *conn, previous.id, change, at).await?;
+    }
+    Ok(row.into_book())
+}
+
+/// Fetches when a book's status last changed, if it ever has.
+async fn last_status_change(
+    conn: &mut SqliteConnection,
+    book_id: i32,
+) -> Result<Option<DateTime<Utc>>> {
+    let changed_at = sqlx::query_scalar(
+        "SELECT changed_at FROM status_event WHERE book_id=? ORDER BY changed_at DESC, id DESC LIMIT 1",
+    )
+    .bind(book_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    Ok(changed_at)
+}
+
+/// Records a change to a book's reading status.
+async fn add_status_event(
+    conn: &mut SqliteConnection,
+    book_id: i32,
+    status: ReadingStatus,
+    at: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query("INSERT INTO status_event(book_id, status, changed_at) VALUES (?, ?, ?)")
+        .bind(book_id)
+        .bind(status)
+        .bind(at)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Starts, ends or drops one of a book's readings.
+async fn apply_reading_change(
+    conn: &mut SqliteConnection,
+    book_id: i32,
+    change: ReadingChange,
+    at: DateTime<Utc>,
+) -> Result<()> {
+    match change {
+        ReadingChange::Start => {
+            sqlx::query("INSERT INTO reading(book_id, started_at) VALUES (?, ?)")
+                .bind(book_id)
+                .bind(at)
+                .execute(&mut *conn)
+                .await
+                .map_err(reading_write_error)?;
+        }
+        ReadingChange::End(outcome) => {
+            let result = sqlx::query(
+                "UPDATE reading SET finished_at=?2, outcome=?3 WHERE book_id=?1 AND outcome IS NULL",
+            )
+            .bind(book_id)
+            .bind(at)
+            .bind(outcome)
+            .execute(&mut *conn)
+            .await?;
+            if result.rows_affected() == 0 {
+                sqlx::query("INSERT INTO reading(book_id, finished_at, outcome) VALUES (?, ?, ?)")
+                    .bind(book_id)
+                    .bind(at)
+                    .bind(outcome)
+                    .execute(&mut *conn)
+                    .await?;
+            }
+        }
+        ReadingChange::Discard => {
+            sqlx::query("DELETE FROM reading WHERE book_id=? AND outcome IS NULL")
+                .bind(book_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns an error if there's no book with the ID.
+async fn book_exists(conn: &mut SqliteConnection, book_id: i32) -> Result<()> {
+    sqlx::query("SELECT id FROM book WHERE id=?")
+        .bind(book_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| anyhow!("No book with ID {} found.", book_id))?;
+    Ok(())
+}
+
+/// Maps errors from writing a shelf to messages that can be shown to the user.
+fn shelf_write_error(error: sqlx::Error) -> anyhow::Error {
+    match error {
+        sqlx::Error::Database(error) if error.is_unique_violation() => {
+            anyhow!("A shelf with this name already exists.")
+        }
+        _ => anyhow!("There was an error connecting to the database."),
+    }
+}
+
+/// Maps the error from starting a reading while one is in progress to a `StatusChangeError`.
+fn reading_write_error(error: sqlx::Error) -> anyhow::Error {
+    match error {
+        sqlx::Error::Database(error) if error.is_unique_violation() => {
+            StatusChangeError::AlreadyReading.into()
+        }
+        error => error.into(),
+    }
+}
+
+/// Maps an error from inserting or updating a `book` row to a user-facing error.
+fn book_write_error(error: sqlx::Error) -> anyhow::Error {
+    match error {
+        sqlx::Error::Database(error)
+            if error.is_unique_violation() && error.message().contains("book.isbn_") =>
+        {
+            anyhow!("A book with this ISBN has already been added.")
+        }
+        sqlx::Error::Database(error)
+            if error.is_unique_violation() && error.message().contains("book_external_id.") =>
+        {
+            anyhow!("This book has already been added from its provider.")
+        }
+        sqlx::Error::Database(error) if error.is_unique_violation() => {
+            anyhow!("This book title and author combination has already been added.")
+        }
+        _ => anyhow!("There was an error connecting to the database."),
+    }
+}
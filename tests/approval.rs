@@ -1,51 +1,19 @@
-use borges::generate_app;
-use serde::Serialize;
+use borges::{config::Config, generate_app, repository::BookRepository};
 use serde_json::Value;
 use sqlx::{Pool, Postgres};
-use tower::ServiceExt;
 
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-struct Request {
-    query: String,
-    operation_name: Option<String>,
-    variables: Option<String>,
-}
+mod common;
+
+use common::{
+    check_find_library_books, check_log_progress, check_status_transitions,
+    check_update_author_keeps_credits, run_book_authors, run_merge_authors,
+    run_merge_authors_conflict, run_reading_progress, run_reading_statuses, run_request,
+    run_rereading, run_series, run_status_history, run_tags_and_shelves, Request, StubProvider,
+};
 
 async fn _run_request(request_body: Request, pool: Pool<Postgres>) -> Value {
-    let app = generate_app(pool).await.unwrap();
-
-    let resp = app
-        .oneshot(
-            axum::http::Request::builder()
-                .method(axum::http::Method::POST)
-                .uri("/")
-                .body(axum::body::Body::from(
-                    serde_json::to_string(&request_body).unwrap(),
-                ))
-                .unwrap(),
-        )
-        .await
-        .unwrap();
-
-    let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
-    let result: Value = serde_json::from_slice(&body).unwrap();
-
-    result
-}
-
-// Macro to set the insta test snapshot suffix. This is needed to mark the tests correctly since
-// insta names the snapshots after the `inner` function from `#[sqlx::test]`, marking all test
-// snapshots as `approval__inner.snap`, `approval__inner-2.snap`, etc., which can end up with
-// random ordering and clobbered snapshot file diffs.
-//
-// Pulled from https://insta.rs/docs/patterns/
-macro_rules! set_snapshot_suffix {
-    ($($expr:expr),*) => {
-        let mut settings = insta::Settings::clone_current();
-        settings.set_snapshot_suffix(format!($($expr,)*));
-        let _guard = settings.bind_to_scope();
-    }
+    let app = generate_app(pool, &Config::default()).await.unwrap();
+    run_request(request_body, app).await
 }
 
 #[sqlx::test]
@@ -112,6 +80,36 @@ async fn test_all_books_query(pool: Pool<Postgres>) -> sqlx::Result<()> {
     Ok(())
 }
 
+#[sqlx::test]
+async fn test_all_books_with_notes_query(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("all_books_with_notes_query");
+
+    let book_query = "
+        query {
+          books {
+            id
+            title
+            notes {
+              id
+              bookId
+              page
+            }
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
 #[sqlx::test]
 async fn test_read_books_query(pool: Pool<Postgres>) -> sqlx::Result<()> {
     set_snapshot_suffix!("read_books_query");
@@ -324,3 +322,1101 @@ async fn test_book_status_mutation(pool: Pool<Postgres>) -> sqlx::Result<()> {
 
     Ok(())
 }
+
+#[sqlx::test]
+async fn test_update_book_mutation(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("update_book_mutation");
+
+    let mutation = "
+        mutation {
+          updateBook(input: {bookId: 3, title: \"White Teeth: A Novel\", year: 2000, imageUrl: \"https://example.com/white-teeth.jpg\"}) {
+            book {
+              id
+              title
+              author
+              imageUrl
+              year
+              pages
+              status
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_invalid_book_id_update_mutation(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("invalid_book_id_update_mutation");
+
+    let mutation = "
+        mutation {
+          updateBook(input: {bookId: 999, title: \"Ficciones\"}) {
+            book {
+              id
+              title
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_duplicate_update_book_mutation(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("duplicate_update_book_mutation");
+
+    let mutation = "
+        mutation {
+          updateBook(input: {bookId: 3, title: \"Blood Meridian\", author: \"Cormac McCarthy\"}) {
+            book {
+              id
+              title
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_invalid_pages_update_mutation(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("invalid_pages_update_mutation");
+
+    let mutation = "
+        mutation {
+          updateBook(input: {bookId: 3, pages: 0}) {
+            book {
+              id
+              pages
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_delete_book_mutation(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("delete_book_mutation");
+
+    let mutation = "
+        mutation {
+          deleteBook(bookId: 2) {
+            bookId
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool.clone()).await;
+    insta::assert_json_snapshot!(result);
+
+    let notes: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM note WHERE book_id = 2")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(notes, 0);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_invalid_book_id_delete_mutation(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("invalid_book_id_delete_mutation");
+
+    let mutation = "
+        mutation {
+          deleteBook(bookId: 999) {
+            bookId
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_update_note_mutation(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("update_note_mutation");
+
+    let mutation = "
+        mutation {
+          updateNote(input: {noteId: 2, note: \"updated note!\", page: 12}) {
+            note {
+              id
+              bookId
+              note
+              page
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_clear_note_page_mutation(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("clear_note_page_mutation");
+
+    let mutation = "
+        mutation {
+          updateNote(input: {noteId: 2, page: null}) {
+            note {
+              id
+              bookId
+              note
+              page
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_invalid_page_update_note_mutation(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("invalid_page_update_note_mutation");
+
+    let mutation = "
+        mutation {
+          updateNote(input: {noteId: 1, page: 9999}) {
+            note {
+              id
+              page
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_invalid_note_id_update_mutation(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("invalid_note_id_update_mutation");
+
+    let mutation = "
+        mutation {
+          updateNote(input: {noteId: 999, note: \"updated note!\"}) {
+            note {
+              id
+              note
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_delete_note_mutation(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("delete_note_mutation");
+
+    let mutation = "
+        mutation {
+          deleteNote(noteId: 1) {
+            noteId
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_invalid_note_id_delete_mutation(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("invalid_note_id_delete_mutation");
+
+    let mutation = "
+        mutation {
+          deleteNote(noteId: 999) {
+            noteId
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_books_connection_first_page_query(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("books_connection_first_page_query");
+
+    let book_query = "
+        query {
+          booksConnection(first: 2) {
+            edges {
+              cursor
+              node {
+                id
+                title
+              }
+            }
+            pageInfo {
+              hasPreviousPage
+              hasNextPage
+              startCursor
+              endCursor
+            }
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_books_connection_after_cursor_query(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("books_connection_after_cursor_query");
+
+    let book_query = "
+        query {
+          booksConnection(first: 2, after: \"Mg\") {
+            edges {
+              cursor
+              node {
+                id
+                title
+              }
+            }
+            pageInfo {
+              hasPreviousPage
+              hasNextPage
+              startCursor
+              endCursor
+            }
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_books_connection_last_page_query(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("books_connection_last_page_query");
+
+    let book_query = "
+        query {
+          booksConnection(status: UNREAD, last: 2) {
+            edges {
+              cursor
+              node {
+                id
+                title
+                status
+              }
+            }
+            pageInfo {
+              hasPreviousPage
+              hasNextPage
+            }
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_notes_connection_query(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("notes_connection_query");
+
+    let book_query = "
+        query {
+          book(bookId: 2) {
+            notesConnection(first: 1) {
+              edges {
+                node {
+                  id
+                  page
+                }
+              }
+              pageInfo {
+                hasNextPage
+                endCursor
+              }
+            }
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_books_connection_page_size_too_large_query(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("books_connection_page_size_too_large_query");
+
+    let book_query = "
+        query {
+          booksConnection(first: 1000) {
+            edges {
+              node {
+                id
+              }
+            }
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_filtered_sorted_books_query(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("filtered_sorted_books_query");
+
+    let book_query = "
+        query {
+          books(filter: {minYear: 1980, maxPages: 500}, sort: TITLE, direction: DESCENDING) {
+            id
+            title
+            year
+            pages
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_title_author_filter_books_query(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("title_author_filter_books_query");
+
+    let book_query = "
+        query {
+          books(filter: {title: \"RAIN\", author: \"pynchon\"}) {
+            id
+            title
+            author
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_status_and_statuses_filter_books_query(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("status_and_statuses_filter_books_query");
+
+    let book_query = "
+        query {
+          books(status: READ, filter: {statuses: [UNREAD, READ]}) {
+            id
+            title
+            status
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_year_sorted_books_query(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("year_sorted_books_query");
+
+    let book_query = "
+        query {
+          books(sort: YEAR) {
+            id
+            title
+            year
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_library_search_notes_query(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("library_search_notes_query");
+
+    let book_query = "
+        query {
+            librarySearch(query: \"dolor\") {
+                matched
+                snippet
+                book {
+                    title
+                }
+                note {
+                    id
+                    page
+                }
+            }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_library_search_books_query(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("library_search_books_query");
+
+    let book_query = "
+        query {
+            librarySearch(query: \"borges fictions\") {
+                matched
+                snippet
+                book {
+                    id
+                    title
+                    author
+                }
+            }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_library_search_empty_query(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("library_search_empty_query");
+
+    let book_query = "
+        query {
+            librarySearch(query: \"   \") {
+                snippet
+            }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_find_library_books(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    check_find_library_books(&BookRepository::new(pool)).await;
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_update_author_keeps_credits(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    check_update_author_keeps_credits(&BookRepository::new(pool)).await;
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_add_book_isbn_mutation(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("add_book_isbn_mutation");
+
+    let mutation = "
+        mutation {
+            addBook(input: { title: \"Gravity's Rainbow (Penguin Classics)\", author: \"Thomas Pynchon\", year: 2006, pages: 776, isbn10: \"0-14-303994-6\" }) {
+                book {
+                    title
+                    isbn10
+                    isbn13
+                }
+                success
+            }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_add_book_invalid_isbn_mutation(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("add_book_invalid_isbn_mutation");
+
+    let mutation = "
+        mutation {
+            addBook(input: { title: \"Dune\", author: \"Frank Herbert\", year: 1965, pages: 412, isbn13: \"9780441172718\" }) {
+                success
+            }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_add_book_mismatched_isbns_mutation(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("add_book_mismatched_isbns_mutation");
+
+    let mutation = "
+        mutation {
+            addBook(input: { title: \"Dune\", author: \"Frank Herbert\", year: 1965, pages: 412, isbn10: \"0441172717\", isbn13: \"9780143039945\" }) {
+                success
+            }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_add_book_duplicate_isbn_mutation(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("add_book_duplicate_isbn_mutation");
+
+    let mutation = "
+        mutation {
+            addBook(input: { title: \"Gravity's Rainbow (Penguin Classics)\", author: \"Thomas Pynchon\", year: 2006, pages: 776, isbn13: \"978-0-14-303994-5\" }) {
+                success
+            }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    sqlx::query("UPDATE book SET isbn_10 = '0143039946', isbn_13 = '9780143039945' WHERE id = 2")
+        .execute(&pool)
+        .await?;
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_book_by_isbn_query(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("book_by_isbn_query");
+
+    let book_query = "
+        query {
+            book(isbn: \"0-14-303994-6\") {
+                id
+                title
+                isbn10
+                isbn13
+            }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    sqlx::query("UPDATE book SET isbn_10 = '0143039946', isbn_13 = '9780143039945' WHERE id = 2")
+        .execute(&pool)
+        .await?;
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_persistent_metadata_cache(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("persistent_metadata_cache");
+
+    let stub = StubProvider::start().await;
+    let config = Config {
+        metadata_cache_persistent: true,
+        ..stub.config()
+    };
+    let search = || Request {
+        query: "
+            query {
+                search(query: \"gravity's rainbow\") {
+                    id
+                    title
+                }
+            }
+        "
+        .to_string(),
+        operation_name: None,
+        variables: None,
+    };
+
+    let app = generate_app(pool.clone(), &config).await.unwrap();
+    let first = run_request(search(), app).await;
+
+    // A new app, like after a restart, answers the search from the database.
+    let app = generate_app(pool, &config).await.unwrap();
+    let second = run_request(search(), app.clone()).await;
+    assert_eq!(first, second);
+    assert_eq!(1, stub.requests());
+
+    let stats_query = "
+        query {
+            metadataCacheStats {
+                hits
+                persistentHits
+                misses
+                entries
+            }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: stats_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = run_request(body, app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_refresh_all_book_metadata(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("refresh_all_book_metadata");
+
+    let stub = StubProvider::start().await;
+    let app = generate_app(pool.clone(), &stub.config()).await.unwrap();
+    let request = |query: &str| Request {
+        query: query.to_string(),
+        operation_name: None,
+        variables: None,
+    };
+
+    run_request(
+        request(
+            "
+            mutation {
+                addExternalBook(input: { provider: GOOGLE_BOOKS, externalId: \"zyTCAlFPjgYC\" }) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    // The provider's data has changed since the book was added.
+    sqlx::query("UPDATE book SET pages = 200, image_url = NULL WHERE title = 'The Google Story'")
+        .execute(&pool)
+        .await?;
+
+    let result = run_request(
+        request(
+            "
+            mutation {
+                refreshAllBookMetadata {
+                    book {
+                        id
+                        title
+                        year
+                        pages
+                        imageUrl
+                    }
+                    changes {
+                        field
+                        oldValue
+                        newValue
+                    }
+                    skipped {
+                        field
+                        oldValue
+                        newValue
+                    }
+                    error
+                }
+            }
+            ",
+        ),
+        app,
+    )
+    .await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_book_authors(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("book_authors");
+
+    let app = generate_app(pool, &Config::default()).await.unwrap();
+    let result = run_book_authors(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_merge_authors(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("merge_authors");
+
+    let app = generate_app(pool, &Config::default()).await.unwrap();
+    let result = run_merge_authors(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_merge_authors_conflict(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("merge_authors_conflict");
+
+    let app = generate_app(pool, &Config::default()).await.unwrap();
+    let result = run_merge_authors_conflict(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_tags_and_shelves(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("tags_and_shelves");
+
+    let app = generate_app(pool, &Config::default()).await.unwrap();
+    let result = run_tags_and_shelves(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_series_query(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("series_query");
+
+    let app = generate_app(pool, &Config::default()).await.unwrap();
+    let result = run_series(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_reading_statuses(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("reading_statuses");
+
+    let app = generate_app(pool, &Config::default()).await.unwrap();
+    let result = run_reading_statuses(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_status_transitions(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    check_status_transitions(&BookRepository::new(pool)).await;
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_status_history(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("status_history");
+
+    let app = generate_app(pool, &Config::default()).await.unwrap();
+    let result = run_status_history(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_rereading(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("rereading");
+
+    let app = generate_app(pool, &Config::default()).await.unwrap();
+    let result = run_rereading(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_log_progress(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    check_log_progress(&BookRepository::new(pool)).await;
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_reading_progress(pool: Pool<Postgres>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("reading_progress");
+
+    let app = generate_app(pool, &Config::default()).await.unwrap();
+    let result = run_reading_progress(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
@@ -0,0 +1,1308 @@
+use std::net::SocketAddr;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use async_graphql::MaybeUndefined;
+use axum::{
+    http::header::CONTENT_TYPE, response::IntoResponse, routing::get, Extension, Router, Server,
+};
+use borges::{
+    config::Config,
+    graphql::{
+        AddBookInput, AuthorRole, BookAuthorInput, ExternalProvider, ReadingOutcome, ReadingStatus,
+        UpdateBookInput,
+    },
+    repository::{BookStore, LibraryBookKey, StatusChangeError},
+};
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Request {
+    pub query: String,
+    pub operation_name: Option<String>,
+    pub variables: Option<String>,
+}
+
+pub async fn run_request(request_body: Request, app: Router) -> Value {
+    let resp = app
+        .oneshot(
+            axum::http::Request::builder()
+                .method(axum::http::Method::POST)
+                .uri("/")
+                .body(axum::body::Body::from(
+                    serde_json::to_string(&request_body).unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+    let result: Value = serde_json::from_slice(&body).unwrap();
+
+    result
+}
+
+/// Looks up an author's ID by name, since each backend numbers the seed data's authors its own way.
+pub async fn author_id(app: Router, name: &str) -> i64 {
+    let request = Request {
+        query: "{ authors { id name } }".to_string(),
+        operation_name: None,
+        variables: None,
+    };
+    let result = run_request(request, app).await;
+
+    result["data"]["authors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|author| author["name"] == name)
+        .and_then(|author| author["id"].as_i64())
+        .unwrap()
+}
+
+/// Checks that `find_library_books` finds books by external ID, ISBN and title and author against
+/// any `BookStore` seeded with the sample data.
+pub async fn check_find_library_books(repository: &dyn BookStore) {
+    let added = repository
+        .add_book(AddBookInput {
+            title: "Gravity's Rainbow (Penguin Classics)".to_string(),
+            author: "Thomas Pynchon".to_string(),
+            image_url: None,
+            year: 2006,
+            pages: 776,
+            status: None,
+            isbn_10: Some("0143039946".to_string()),
+            isbn_13: Some("9780143039945".to_string()),
+            external_provider: Some(ExternalProvider::GoogleBooks),
+            external_id: Some("4CXGDwAAQBAJ".to_string()),
+            authors: None,
+            overridden_fields: Vec::new(),
+        })
+        .await
+        .unwrap();
+
+    let key = |provider, external_id: &str, isbn_13: Option<&str>, title: &str, author: &str| {
+        LibraryBookKey {
+            provider,
+            external_id: external_id.to_string(),
+            isbn_10: None,
+            isbn_13: isbn_13.map(str::to_string),
+            title: title.to_string(),
+            author: Some(author.to_string()),
+        }
+    };
+    let keys = [
+        // By external ID, even though the title differs
+        key(
+            ExternalProvider::GoogleBooks,
+            "4CXGDwAAQBAJ",
+            None,
+            "Gravity's Rainbow",
+            "Pynchon",
+        ),
+        // By ISBN from another provider
+        key(
+            ExternalProvider::OpenLibrary,
+            "OL45804W",
+            Some("9780143039945"),
+            "Gravity",
+            "T. Pynchon",
+        ),
+        // By title and author
+        key(
+            ExternalProvider::OpenLibrary,
+            "OL1W",
+            None,
+            "Blood Meridian",
+            "Cormac McCarthy",
+        ),
+        // Not in the library
+        key(
+            ExternalProvider::OpenLibrary,
+            "OL2W",
+            Some("9780000000002"),
+            "Dune",
+            "Frank Herbert",
+        ),
+    ];
+
+    let books = repository.find_library_books(&keys).await.unwrap();
+    let matches: Vec<Option<i32>> = keys
+        .iter()
+        .map(|key| key.find_match(&books).map(|book| book.id))
+        .collect();
+
+    assert_eq!(vec![Some(added.id), Some(added.id), Some(4), None], matches);
+}
+
+/// Checks that updating just a book's `author` replaces its primary credit and keeps the rest,
+/// against any `BookStore` seeded with the sample data.
+pub async fn check_update_author_keeps_credits(repository: &dyn BookStore) {
+    let update = |author: Option<&str>, authors: Option<Vec<BookAuthorInput>>| UpdateBookInput {
+        book_id: 2,
+        title: None,
+        author: author.map(str::to_string),
+        image_url: MaybeUndefined::Undefined,
+        year: None,
+        pages: None,
+        authors,
+        overridden_fields: Vec::new(),
+    };
+    repository
+        .update_book(update(
+            Some("Thomas Pynchon"),
+            Some(vec![
+                BookAuthorInput::author("Thomas Pynchon".to_string()),
+                BookAuthorInput {
+                    name: "Elfriede Jelinek".to_string(),
+                    role: AuthorRole::Translator,
+                },
+            ]),
+        ))
+        .await
+        .unwrap();
+
+    let book = repository
+        .update_book(update(Some("T. Pynchon"), None))
+        .await
+        .unwrap();
+    let credits: Vec<(String, AuthorRole)> = repository
+        .get_book_authors(&[2])
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|credit| (credit.author.name, credit.role))
+        .collect();
+
+    assert_eq!("T. Pynchon", book.author);
+    assert_eq!(
+        vec![
+            ("T. Pynchon".to_string(), AuthorRole::Author),
+            ("Elfriede Jelinek".to_string(), AuthorRole::Translator),
+        ],
+        credits
+    );
+}
+
+/// Checks that `update_book_status` and `start_rereading` themselves enforce the allowed
+/// transitions, leave a book's history alone when its status is set to the one it has and keep
+/// the history in order, against any `BookStore` seeded with the sample data.
+pub async fn check_status_transitions(repository: &dyn BookStore) {
+    let history = repository.get_status_history(&[1]).await.unwrap();
+
+    // Collected Fictions is READ, and only books being read can be paused.
+    let error = repository
+        .update_book_status(1, ReadingStatus::Paused, Utc::now())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        Some(&StatusChangeError::Transition {
+            from: ReadingStatus::Read,
+            to: ReadingStatus::Paused,
+        }),
+        error.downcast_ref::<StatusChangeError>()
+    );
+
+    let book = repository
+        .update_book_status(1, ReadingStatus::Read, Utc::now())
+        .await
+        .unwrap();
+    assert_eq!(ReadingStatus::Read, book.status);
+    assert_eq!(history, repository.get_status_history(&[1]).await.unwrap());
+
+    // Nor can a change be backdated to before the book's last one.
+    let now = Utc::now();
+    repository
+        .update_book_status(1, ReadingStatus::Unread, now)
+        .await
+        .unwrap();
+    let error = repository
+        .update_book_status(1, ReadingStatus::Reading, now - Duration::days(1))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        Some(&StatusChangeError::Backdated),
+        error.downcast_ref::<StatusChangeError>()
+    );
+
+    // Only READ books can be reread, which is checked along with the change too.
+    let error = repository.start_rereading(1, now).await.unwrap_err();
+    assert_eq!(
+        Some(&StatusChangeError::NotRead),
+        error.downcast_ref::<StatusChangeError>()
+    );
+}
+
+/// Checks that `log_progress` only logs progress for a book being read, and that reaching the
+/// last page finishes the book and its reading in the same change, against any `BookStore` seeded
+/// with the sample data.
+pub async fn check_log_progress(repository: &dyn BookStore) {
+    let not_reading = |error: anyhow::Error| {
+        error.downcast_ref::<StatusChangeError>() == Some(&StatusChangeError::NotReading)
+    };
+    // Collected Fictions is READ.
+    assert!(not_reading(
+        repository
+            .log_progress(1, 10, Utc::now())
+            .await
+            .unwrap_err()
+    ));
+
+    // Gravity's Rainbow has 776 pages.
+    let started = Utc::now() - Duration::hours(2);
+    repository
+        .update_book_status(2, ReadingStatus::Reading, started)
+        .await
+        .unwrap();
+    let error = repository
+        .log_progress(2, 100, started - Duration::hours(1))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        Some(&StatusChangeError::ProgressBackdated),
+        error.downcast_ref::<StatusChangeError>()
+    );
+
+    let (book, entry) = repository
+        .log_progress(2, 100, started + Duration::hours(1))
+        .await
+        .unwrap();
+    assert_eq!(ReadingStatus::Reading, book.status);
+    assert_eq!(100, entry.page);
+
+    let finished = Utc::now();
+    let (book, _) = repository.log_progress(2, 776, finished).await.unwrap();
+    assert_eq!(ReadingStatus::Read, book.status);
+    let reading = repository.get_readings(&[2]).await.unwrap().pop().unwrap();
+    assert_eq!(Some(ReadingOutcome::Finished), reading.outcome);
+    assert!(not_reading(
+        repository.log_progress(2, 776, finished).await.unwrap_err()
+    ));
+}
+
+/// Adds a book credited to two authors and an illustrator, then reorders its credits and tries
+/// crediting an author twice and giving an `author` that isn't the primary credit, against an app
+/// seeded with the sample data. Returns every book's credits, since the seed data's authors are
+/// credited too.
+pub async fn run_book_authors(app: Router) -> Value {
+    let request = |query: &str| Request {
+        query: query.to_string(),
+        operation_name: None,
+        variables: None,
+    };
+
+    let add = run_request(
+        request(
+            "
+            mutation {
+                addBook(input: { title: \"Good Omens\", author: \"Terry Pratchett\", year: 1990, pages: 288, authors: [{ name: \"Terry Pratchett\" }, { name: \"Neil Gaiman\" }, { name: \"Paul Kidby\", role: ILLUSTRATOR }] }) {
+                    book {
+                        id
+                    }
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(Value::Null, add["errors"]);
+    let book_id = &add["data"]["addBook"]["book"]["id"];
+
+    let duplicate = run_request(
+        request(&format!(
+            "
+            mutation {{
+                updateBook(input: {{ bookId: {book_id}, authors: [{{ name: \"Neil Gaiman\" }}, {{ name: \"Neil Gaiman\" }}] }}) {{
+                    success
+                }}
+            }}
+            ",
+        )),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(
+        "An author can only be credited once per role.",
+        duplicate["errors"][0]["message"]
+    );
+
+    // Replacing the credits also updates the primary author.
+    let reordered = run_request(
+        request(&format!(
+            "
+            mutation {{
+                updateBook(input: {{ bookId: {book_id}, authors: [{{ name: \"Neil Gaiman\" }}, {{ name: \"Terry Pratchett\" }}, {{ name: \"Paul Kidby\", role: ILLUSTRATOR }}] }}) {{
+                    book {{
+                        author
+                    }}
+                }}
+            }}
+            ",
+        )),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(
+        "Neil Gaiman",
+        reordered["data"]["updateBook"]["book"]["author"]
+    );
+
+    // `author` can't disagree with the primary credit in `authors`.
+    let mismatched_add = run_request(
+        request(
+            "
+            mutation {
+                addBook(input: { title: \"The Talisman\", author: \"Peter Straub\", year: 1984, pages: 646, authors: [{ name: \"Stephen King\" }, { name: \"Peter Straub\" }] }) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(
+        "Author must be the primary author in authors.",
+        mismatched_add["errors"][0]["message"]
+    );
+    let mismatched_update = run_request(
+        request(&format!(
+            "
+            mutation {{
+                updateBook(input: {{ bookId: {book_id}, author: \"Terry Pratchett\", authors: [{{ name: \"Neil Gaiman\" }}, {{ name: \"Terry Pratchett\" }}] }}) {{
+                    success
+                }}
+            }}
+            ",
+        )),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(
+        "Author must be the primary author in authors.",
+        mismatched_update["errors"][0]["message"]
+    );
+
+    run_request(
+        request(
+            "
+            {
+                books {
+                    title
+                    author
+                    authors {
+                        author {
+                            name
+                            books {
+                                title
+                            }
+                        }
+                        role
+                    }
+                }
+            }
+            ",
+        ),
+        app,
+    )
+    .await
+}
+
+/// Merges "J. L. Borges" into "Jorge Luis Borges" and tries merging an author into themselves,
+/// against an app seeded with the sample data. Returns the authors and the merged author's books.
+pub async fn run_merge_authors(app: Router) -> Value {
+    let request = |query: &str| Request {
+        query: query.to_string(),
+        operation_name: None,
+        variables: None,
+    };
+
+    run_request(
+        request(
+            "
+            mutation {
+                addBook(input: { title: \"Labyrinths\", author: \"J. L. Borges\", year: 1962, pages: 256 }) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    let source_id = author_id(app.clone(), "J. L. Borges").await;
+    let target_id = author_id(app.clone(), "Jorge Luis Borges").await;
+    let merge = run_request(
+        request(&format!(
+            "
+            mutation {{
+                mergeAuthors(sourceIds: [{source_id}], targetId: {target_id}) {{
+                    success
+                }}
+            }}
+            ",
+        )),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(Value::Bool(true), merge["data"]["mergeAuthors"]["success"]);
+
+    let query = format!("{{ author(id: {source_id}) {{ name }} }}");
+    let merged = run_request(request(&query), app.clone()).await;
+    assert_eq!(Value::Null, merged["data"]["author"]);
+
+    let invalid = run_request(
+        request(&format!(
+            "
+            mutation {{
+                mergeAuthors(sourceIds: [{target_id}], targetId: {target_id}) {{
+                    success
+                }}
+            }}
+            ",
+        )),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(
+        "An author can't be merged into themselves.",
+        invalid["errors"][0]["message"]
+    );
+
+    run_request(
+        request(&format!(
+            "
+            {{
+                authors {{
+                    id
+                    name
+                    bookCount
+                    readCount
+                }}
+                author(id: {target_id}) {{
+                    books {{
+                        title
+                        author
+                    }}
+                }}
+            }}
+            ",
+        )),
+        app,
+    )
+    .await
+}
+
+/// Tries a merge that would give an author two books with the same title, against an app seeded
+/// with the sample data. Returns the authors and books, which the failed merge leaves alone.
+pub async fn run_merge_authors_conflict(app: Router) -> Value {
+    let request = |query: &str| Request {
+        query: query.to_string(),
+        operation_name: None,
+        variables: None,
+    };
+
+    // Merging would give Borges two copies of "Collected Fictions", so nothing is merged.
+    run_request(
+        request(
+            "
+            mutation {
+                labyrinths: addBook(input: { title: \"Labyrinths\", author: \"J. L. Borges\", year: 1962, pages: 256 }) {
+                    success
+                }
+                fictions: addBook(input: { title: \"Collected Fictions\", author: \"J. L. Borges\", year: 1998, pages: 565 }) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    let source_id = author_id(app.clone(), "J. L. Borges").await;
+    let target_id = author_id(app.clone(), "Jorge Luis Borges").await;
+    let merge = run_request(
+        request(&format!(
+            "
+            mutation {{
+                mergeAuthors(sourceIds: [{source_id}], targetId: {target_id}) {{
+                    success
+                }}
+            }}
+            ",
+        )),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(
+        "This book title and author combination has already been added.",
+        merge["errors"][0]["message"]
+    );
+
+    run_request(
+        request(
+            "
+            {
+                authors {
+                    id
+                    name
+                    bookCount
+                }
+                books {
+                    title
+                    author
+                }
+            }
+            ",
+        ),
+        app,
+    )
+    .await
+}
+
+/// Tags books, fills and reorders a shelf and tries a duplicate shelf name, against an app seeded
+/// with the sample data. Returns the tags, shelves and filtered books from before the shelf is
+/// deleted.
+pub async fn run_tags_and_shelves(app: Router) -> Value {
+    let request = |query: &str| Request {
+        query: query.to_string(),
+        operation_name: None,
+        variables: None,
+    };
+
+    let mutations = run_request(
+        request(
+            "
+            mutation {
+                tagBorges: addBookTags(bookId: 1, tags: [\"argentina\", \" short stories \", \"favourite\", \"argentina\"]) {
+                    book {
+                        tags
+                    }
+                }
+                tagPynchon: addBookTags(bookId: 2, tags: [\"postmodern\", \"favourite\"]) {
+                    book {
+                        tags
+                    }
+                }
+                untagBorges: removeBookTags(bookId: 1, tags: [\"short stories\"]) {
+                    book {
+                        tags
+                    }
+                }
+                createShelf(name: \" Favorites \") {
+                    shelf {
+                        id
+                        name
+                    }
+                }
+                renameShelf(shelfId: 1, name: \"Desert island\") {
+                    shelf {
+                        name
+                    }
+                }
+                addPynchon: addBookToShelf(shelfId: 1, bookId: 2) {
+                    success
+                }
+                addBorges: addBookToShelf(shelfId: 1, bookId: 1) {
+                    success
+                }
+                addMcCarthy: addBookToShelf(shelfId: 1, bookId: 4, position: 0) {
+                    shelf {
+                        books {
+                            title
+                        }
+                    }
+                }
+                moveBorges: addBookToShelf(shelfId: 1, bookId: 1, position: 1) {
+                    shelf {
+                        books {
+                            title
+                        }
+                    }
+                }
+                removeMcCarthy: removeBookFromShelf(shelfId: 1, bookId: 4) {
+                    shelf {
+                        books {
+                            title
+                        }
+                    }
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(Value::Null, mutations["errors"]);
+    let duplicate = run_request(
+        request(
+            "
+            mutation {
+                createShelf(name: \"Desert island\") {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(
+        "A shelf with this name already exists.",
+        duplicate["errors"][0]["message"]
+    );
+
+    let result = run_request(
+        request(
+            "
+            {
+                tags
+                shelves {
+                    id
+                    name
+                    books {
+                        title
+                        tags
+                    }
+                }
+                favourites: books(filter: { tags: [\"favourite\"] }) {
+                    title
+                }
+                onShelf: books(filter: { shelf: 1 }, sort: TITLE) {
+                    title
+                }
+                both: books(filter: { tags: [\"favourite\", \"postmodern\"], shelf: 1 }) {
+                    title
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+
+    // Deleting a shelf leaves its books in the library.
+    run_request(
+        request(
+            "
+            mutation {
+                deleteShelf(shelfId: 1) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    let after_delete = run_request(request("{ shelves { id } books { id } }"), app).await;
+    assert_eq!(0, after_delete["data"]["shelves"].as_array().unwrap().len());
+    assert_eq!(4, after_delete["data"]["books"].as_array().unwrap().len());
+
+    result
+}
+
+/// Adds a series of four books, one at a fractional position, and tries a negative position and
+/// removing a book from the series, against an app seeded with the sample data. Returns the series
+/// as it was before the book was removed.
+pub async fn run_series(app: Router) -> Value {
+    let request = |query: &str| Request {
+        query: query.to_string(),
+        operation_name: None,
+        variables: None,
+    };
+
+    let setup = run_request(
+        request(
+            "
+            mutation {
+                createSeries(name: \"The Murderbot Diaries\") {
+                    series {
+                        id
+                    }
+                }
+                first: addBook(input: { title: \"All Systems Red\", author: \"Martha Wells\", year: 2017, pages: 144, status: READ }) {
+                    book {
+                        id
+                    }
+                }
+                second: addBook(input: { title: \"Artificial Condition\", author: \"Martha Wells\", year: 2018, pages: 158, status: READING }) {
+                    book {
+                        id
+                    }
+                }
+                novella: addBook(input: { title: \"Home: Habitat, Range, Niche, Territory\", author: \"Martha Wells\", year: 2021, pages: 40 }) {
+                    book {
+                        id
+                    }
+                }
+                fourth: addBook(input: { title: \"Exit Strategy\", author: \"Martha Wells\", year: 2018, pages: 172 }) {
+                    book {
+                        id
+                    }
+                }
+                setFirst: setBookSeries(bookId: 5, seriesId: 1, position: 1) {
+                    success
+                }
+                setSecond: setBookSeries(bookId: 6, seriesId: 1, position: 2) {
+                    success
+                }
+                setNovella: setBookSeries(bookId: 7, seriesId: 1, position: 4.5) {
+                    success
+                }
+                setFourth: setBookSeries(bookId: 8, seriesId: 1, position: 4) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(Value::Null, setup["errors"]);
+
+    let result = run_request(
+        request(
+            "
+            {
+                series(id: 1) {
+                    name
+                    books {
+                        title
+                        status
+                        seriesPosition
+                        series {
+                            name
+                        }
+                        nextInSeries {
+                            title
+                        }
+                    }
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+
+    let invalid = run_request(
+        request(
+            "
+            mutation {
+                setBookSeries(bookId: 1, seriesId: 1, position: -1) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(
+        "Series position cannot be negative.",
+        invalid["errors"][0]["message"]
+    );
+
+    // Books outside a series have no series fields.
+    let removed = run_request(
+        request(
+            "
+            mutation {
+                removeBookFromSeries(bookId: 8) {
+                    book {
+                        series {
+                            name
+                        }
+                        seriesPosition
+                        nextInSeries {
+                            title
+                        }
+                    }
+                }
+            }
+            ",
+        ),
+        app,
+    )
+    .await;
+    assert_eq!(
+        json!({ "series": null, "seriesPosition": null, "nextInSeries": null }),
+        removed["data"]["removeBookFromSeries"]["book"]
+    );
+
+    result
+}
+
+/// Adds a WISHLIST book, pauses another and tries a change that isn't allowed, against an app
+/// seeded with the sample data. Returns the books by status for the caller to snapshot.
+pub async fn run_reading_statuses(app: Router) -> Value {
+    let request = |query: &str| Request {
+        query: query.to_string(),
+        operation_name: None,
+        variables: None,
+    };
+
+    let setup = run_request(
+        request(
+            "
+            mutation {
+                addBook(input: { title: \"The Left Hand of Darkness\", author: \"Ursula K. Le Guin\", year: 1969, pages: 304, status: WISHLIST }) {
+                    book {
+                        id
+                    }
+                }
+                start: updateBookStatus(input: { bookId: 2, status: READING }) {
+                    success
+                }
+                pause: updateBookStatus(input: { bookId: 2, status: PAUSED }) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(Value::Null, setup["errors"]);
+
+    // Only books that have been started can be given up on.
+    let rejected = run_request(
+        request(
+            "
+            mutation {
+                updateBookStatus(input: { bookId: 3, status: DID_NOT_FINISH }) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(
+        "A book's status can't be changed from unread to did_not_finish.",
+        rejected["errors"][0]["message"]
+    );
+
+    run_request(
+        request(
+            "
+            {
+                books {
+                    title
+                    status
+                }
+                wishlist: books(status: WISHLIST) {
+                    title
+                    status
+                }
+            }
+            ",
+        ),
+        app,
+    )
+    .await
+}
+
+/// Changes a book's status a few times, some backdated, against an app seeded with the sample
+/// data. Returns the books' status histories.
+pub async fn run_status_history(app: Router) -> Value {
+    let request = |query: &str| Request {
+        query: query.to_string(),
+        operation_name: None,
+        variables: None,
+    };
+
+    let setup = run_request(
+        request(
+            "
+            mutation {
+                start: updateBookStatus(input: { bookId: 2, status: READING, at: \"2023-01-02T10:00:00Z\" }) {
+                    success
+                }
+                pause: updateBookStatus(input: { bookId: 2, status: PAUSED, at: \"2023-01-10T21:30:00Z\" }) {
+                    success
+                }
+                resume: updateBookStatus(input: { bookId: 2, status: READING, at: \"2023-01-15T08:00:00Z\" }) {
+                    success
+                }
+                finish: updateBookStatus(input: { bookId: 2, status: READ, at: \"2023-02-01T22:15:00Z\" }) {
+                    success
+                }
+                addBook(input: { title: \"The Left Hand of Darkness\", author: \"Ursula K. Le Guin\", year: 1969, pages: 304, status: WISHLIST }) {
+                    book {
+                        statusHistory {
+                            status
+                        }
+                    }
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(Value::Null, setup["errors"]);
+    assert_eq!(
+        json!([{ "status": "WISHLIST" }]),
+        setup["data"]["addBook"]["book"]["statusHistory"]
+    );
+
+    // A backdated change can't go before the book's last one.
+    let rejected = run_request(
+        request(
+            "
+            mutation {
+                updateBookStatus(input: { bookId: 2, status: UNREAD, at: \"2023-01-20T00:00:00Z\" }) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(
+        "A status change can't be earlier than the book's last one.",
+        rejected["errors"][0]["message"]
+    );
+
+    run_request(
+        request(
+            "
+            {
+                book(bookId: 2) {
+                    title
+                    status
+                    startedAt
+                    finishedAt
+                    statusHistory {
+                        status
+                        at
+                    }
+                }
+            }
+            ",
+        ),
+        app,
+    )
+    .await
+}
+
+/// Rereads Collected Fictions with a note on the new reading, against an app seeded with the
+/// sample data. Returns the book's readings and their notes.
+pub async fn run_rereading(app: Router) -> Value {
+    let request = |query: &str| Request {
+        query: query.to_string(),
+        operation_name: None,
+        variables: None,
+    };
+
+    let setup = run_request(
+        request(
+            "
+            mutation {
+                startRereading(bookId: 1, at: \"2023-03-01T09:00:00Z\") {
+                    book {
+                        status
+                    }
+                }
+                addNote(input: { bookId: 1, note: \"Tlön reads differently the second time.\", page: 68, readingId: 2 }) {
+                    note {
+                        readingId
+                    }
+                }
+                updateBookStatus(input: { bookId: 1, status: READ, at: \"2023-03-20T18:00:00Z\" }) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(Value::Null, setup["errors"]);
+    assert_eq!("READING", setup["data"]["startRereading"]["book"]["status"]);
+
+    let rejected = run_request(
+        request(
+            "
+            mutation {
+                startRereading(bookId: 2) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(
+        "Only books that have been read can be reread.",
+        rejected["errors"][0]["message"]
+    );
+
+    // Notes can only be attached to a reading of their own book.
+    let rejected = run_request(
+        request(
+            "
+            mutation {
+                addNote(input: { bookId: 2, note: \"Wrong book\", readingId: 2 }) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(
+        "Reading with that ID not found for this book",
+        rejected["errors"][0]["message"]
+    );
+
+    // An existing note can be moved onto a reading of its book, but not of another one.
+    let moved = run_request(
+        request(
+            "
+            mutation {
+                updateNote(input: { noteId: 1, readingId: 1 }) {
+                    note {
+                        readingId
+                    }
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(1, moved["data"]["updateNote"]["note"]["readingId"]);
+
+    let rejected = run_request(
+        request(
+            "
+            mutation {
+                updateNote(input: { noteId: 2, readingId: 2 }) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(
+        "Reading with that ID not found for this book",
+        rejected["errors"][0]["message"]
+    );
+
+    run_request(
+        request(
+            "
+            {
+                book(bookId: 1) {
+                    status
+                    startedAt
+                    finishedAt
+                    readings {
+                        id
+                        startedAt
+                        finishedAt
+                        outcome
+                        notes {
+                            note
+                            page
+                        }
+                    }
+                }
+            }
+            ",
+        ),
+        app,
+    )
+    .await
+}
+
+/// Logs progress on a book by page and by percent up to its last page, against an app seeded with
+/// the sample data. Returns the book's readings and their progress.
+pub async fn run_reading_progress(app: Router) -> Value {
+    let request = |query: &str| Request {
+        query: query.to_string(),
+        operation_name: None,
+        variables: None,
+    };
+
+    let setup = run_request(
+        request(
+            "
+            mutation {
+                updateBookStatus(input: { bookId: 2, status: READING, at: \"2023-04-01T08:00:00Z\" }) {
+                    success
+                }
+                first: logProgress(bookId: 2, page: 97, at: \"2023-04-02T22:00:00Z\") {
+                    book {
+                        currentPage
+                        percentComplete
+                    }
+                }
+                second: logProgress(bookId: 2, percent: 50, at: \"2023-04-05T21:30:00Z\") {
+                    progress {
+                        page
+                    }
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(Value::Null, setup["errors"]);
+    assert_eq!(
+        json!({ "currentPage": 97, "percentComplete": 12.5 }),
+        setup["data"]["first"]["book"]
+    );
+    assert_eq!(388, setup["data"]["second"]["progress"]["page"]);
+
+    // Progress pages follow the same rules as note pages.
+    let rejected = run_request(
+        request(
+            "
+            mutation {
+                logProgress(bookId: 2, page: 800) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(
+        "Progress page number cannot be greater than the highest page count of the book.",
+        rejected["errors"][0]["message"]
+    );
+
+    let rejected = run_request(
+        request(
+            "
+            mutation {
+                logProgress(bookId: 3, page: 10) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    assert_eq!(
+        "Progress can only be logged for books being read.",
+        rejected["errors"][0]["message"]
+    );
+
+    // Logging the last page finishes the book.
+    run_request(
+        request(
+            "
+            mutation {
+                logProgress(bookId: 2, page: 776, at: \"2023-04-20T23:00:00Z\") {
+                    book {
+                        status
+                        currentPage
+                        percentComplete
+                        finishedAt
+                        readings {
+                            outcome
+                            progress {
+                                page
+                                at
+                            }
+                        }
+                    }
+                }
+            }
+            ",
+        ),
+        app,
+    )
+    .await
+}
+
+// Macro to set the insta test snapshot suffix. This is needed to mark the tests correctly since
+// insta names the snapshots after the `inner` function from `#[sqlx::test]`, marking all test
+// snapshots as `approval__inner.snap`, `approval__inner-2.snap`, etc., which can end up with
+// random ordering and clobbered snapshot file diffs.
+//
+// Pulled from https://insta.rs/docs/patterns/
+#[macro_export]
+macro_rules! set_snapshot_suffix {
+    ($($expr:expr),*) => {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_snapshot_suffix(format!($($expr,)*));
+        let _guard = settings.bind_to_scope();
+    }
+}
+
+/// A local stand-in for the Google Books API that serves the fixtures in
+/// `tests/fixtures/google_books` and counts the requests it receives.
+pub struct StubProvider {
+    address: SocketAddr,
+    requests: Arc<AtomicUsize>,
+}
+
+impl StubProvider {
+    pub async fn start() -> Self {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let fixture = |body: &'static str| {
+            move |Extension(requests): Extension<Arc<AtomicUsize>>| async move {
+                requests.fetch_add(1, Ordering::SeqCst);
+                ([(CONTENT_TYPE, "application/json")], body).into_response()
+            }
+        };
+        let app = Router::new()
+            .route(
+                "/books/v1/volumes",
+                get(fixture(include_str!(
+                    "../fixtures/google_books/search.json"
+                ))),
+            )
+            .route(
+                "/books/v1/volumes/:id",
+                get(fixture(include_str!(
+                    "../fixtures/google_books/volume.json"
+                ))),
+            )
+            .layer(Extension(requests.clone()));
+
+        let server =
+            Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(app.into_make_service());
+        let address = server.local_addr();
+        tokio::spawn(server);
+
+        Self { address, requests }
+    }
+
+    /// A config that sends Google Books requests to the stub.
+    pub fn config(&self) -> Config {
+        Config {
+            google_api_key: Some("test-key".to_string()),
+            google_books_api_url: format!("http://{}/books/v1/volumes", self.address),
+            ..Config::default()
+        }
+    }
+
+    /// The number of requests the stub has received.
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,873 @@
+use std::time::{Duration, Instant};
+
+use borges::{config::Config, generate_memory_app, repository::MemoryBookRepository};
+use serde_json::Value;
+
+mod common;
+
+use common::{
+    check_find_library_books, check_log_progress, check_status_transitions,
+    check_update_author_keeps_credits, run_book_authors, run_merge_authors,
+    run_merge_authors_conflict, run_reading_progress, run_reading_statuses, run_request,
+    run_rereading, run_series, run_status_history, run_tags_and_shelves, Request, StubProvider,
+};
+
+async fn _run_request(request_body: Request) -> Value {
+    let app = generate_memory_app(MemoryBookRepository::with_sample_data(), &Config::default())
+        .await
+        .unwrap();
+    run_request(request_body, app).await
+}
+
+#[tokio::test]
+async fn test_book_query() {
+    let book_query = "
+        query {
+          book(bookId: 1) {
+            id
+            title
+            author
+            year
+            pages
+            status
+            notes {
+              id
+              bookId
+              note
+              page
+            }
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_all_books_query() {
+    let book_query = "
+        query {
+          books {
+            id
+            title
+            author
+            year
+            pages
+            status
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_read_books_query() {
+    let book_query = "
+        query {
+          books(status: READ) {
+            id
+            title
+            author
+            year
+            pages
+            status
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_add_note_mutation() {
+    let mutation = "
+        mutation {
+          addNote(input: {bookId: 1, note: \"new note!\", page: 3}) {
+            note {
+              id
+              bookId
+              note
+              page
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_invalid_book_id_note_mutation() {
+    let mutation = "
+        mutation {
+          addNote(input: {bookId: 9999, note: \"new note!\", page: 3}) {
+            note {
+              id
+              bookId
+              note
+              page
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_invalid_page_note_mutation() {
+    let mutation = "
+        mutation {
+          addNote(input: {bookId: 1, note: \"new note!\", page: 9999}) {
+            note {
+              id
+              bookId
+              note
+              page
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_invalid_negative_book_page_note_mutation() {
+    let mutation = "
+        mutation {
+          addNote(input: {bookId: 1, note: \"new note!\", page: -5}) {
+            note {
+              id
+              bookId
+              note
+              page
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_invalid_book_id_status_mutation() {
+    let mutation = "
+        mutation {
+          updateBookStatus(input: {bookId: 999, status: READ}) {
+            book {
+              id
+              title
+              status
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_book_status_mutation() {
+    let mutation = "
+        mutation {
+          updateBookStatus(input: {bookId: 2, status: READ}) {
+            book {
+              id
+              title
+              status
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_duplicate_add_book_mutation() {
+    let mutation = "
+        mutation {
+          addBook(input: {title: \"Blood Meridian\", author: \"Cormac McCarthy\", year: 1985, pages: 351}) {
+            book {
+              id
+              title
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_clear_note_page_mutation() {
+    let mutation = "
+        mutation {
+          updateNote(input: {noteId: 2, page: null}) {
+            note {
+              id
+              bookId
+              note
+              page
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_books_connection_after_cursor_query() {
+    let book_query = "
+        query {
+          booksConnection(first: 2, after: \"Mg\") {
+            edges {
+              cursor
+              node {
+                id
+                title
+              }
+            }
+            pageInfo {
+              hasPreviousPage
+              hasNextPage
+              startCursor
+              endCursor
+            }
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_books_connection_last_page_query() {
+    let book_query = "
+        query {
+          booksConnection(status: UNREAD, last: 2) {
+            edges {
+              cursor
+              node {
+                id
+                title
+                status
+              }
+            }
+            pageInfo {
+              hasPreviousPage
+              hasNextPage
+            }
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_filtered_sorted_books_query() {
+    let book_query = "
+        query {
+          books(filter: {minYear: 1980, maxPages: 500}, sort: TITLE, direction: DESCENDING) {
+            id
+            title
+            year
+            pages
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_year_sorted_books_query() {
+    let book_query = "
+        query {
+          books(sort: YEAR) {
+            id
+            title
+            year
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_library_search_notes_query() {
+    let book_query = "
+        query {
+            librarySearch(query: \"dolor\") {
+                matched
+                snippet
+                book {
+                    title
+                }
+                note {
+                    id
+                    page
+                }
+            }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_search_without_api_key_query() {
+    let book_query = "
+        query {
+            search(query: \"gravity's rainbow\") {
+                id
+                title
+            }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_search_books_without_terms_query() {
+    let book_query = "
+        query {
+            searchBooks(input: { query: \" \", pageSize: 10 }) {
+                totalItems
+            }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_search_books_page_size_too_large_query() {
+    let book_query = "
+        query {
+            searchBooks(input: { intitle: \"dune\", pageSize: 41 }) {
+                totalItems
+            }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_find_library_books() {
+    check_find_library_books(&MemoryBookRepository::with_sample_data()).await;
+}
+
+#[tokio::test]
+async fn test_update_author_keeps_credits() {
+    check_update_author_keeps_credits(&MemoryBookRepository::with_sample_data()).await;
+}
+
+#[tokio::test]
+async fn test_search_metadata_cache() {
+    let stub = StubProvider::start().await;
+    let app = generate_memory_app(MemoryBookRepository::with_sample_data(), &stub.config())
+        .await
+        .unwrap();
+    let search = |bypass_cache: bool| {
+        Request {
+        query: format!(
+            "
+            query {{
+                searchBooks(input: {{ query: \"gravity's rainbow\" }}, bypassCache: {bypass_cache}) {{
+                    totalItems
+                    items {{
+                        id
+                        title
+                    }}
+                }}
+            }}
+            "
+        ),
+        operation_name: None,
+        variables: None,
+    }
+    };
+
+    let first = run_request(search(false), app.clone()).await;
+    let second = run_request(search(false), app.clone()).await;
+    assert_eq!(first, second);
+    assert_eq!(1, stub.requests());
+
+    // Bypassing the cache goes to the provider again.
+    run_request(search(true), app.clone()).await;
+    assert_eq!(2, stub.requests());
+
+    let stats_query = "
+        query {
+            metadataCacheStats {
+                hits
+                persistentHits
+                misses
+                entries
+            }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: stats_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = run_request(body, app).await;
+    insta::assert_json_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_refresh_book_metadata() {
+    let stub = StubProvider::start().await;
+    let app = generate_memory_app(MemoryBookRepository::with_sample_data(), &stub.config())
+        .await
+        .unwrap();
+    let request = |query: &str| Request {
+        query: query.to_string(),
+        operation_name: None,
+        variables: None,
+    };
+
+    run_request(
+        request(
+            "
+            mutation {
+                addExternalBook(input: { provider: GOOGLE_BOOKS, externalId: \"zyTCAlFPjgYC\", title: \"The Google Story (2nd ed.)\", year: 2008 }) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+
+    // The title and year were overridden, so a refresh keeps them.
+    let refresh = run_request(
+        request(
+            "
+            mutation {
+                refreshBookMetadata(bookId: 5) {
+                    book {
+                        id
+                        title
+                        year
+                        pages
+                        imageUrl
+                    }
+                    changes {
+                        field
+                        oldValue
+                        newValue
+                    }
+                    skipped {
+                        field
+                        oldValue
+                        newValue
+                    }
+                    error
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    insta::assert_json_snapshot!("refresh_book_metadata_overridden", refresh);
+
+    // Listing an overridden field refreshes it, and it stops being overridden.
+    let refresh = run_request(
+        request(
+            "
+            mutation {
+                refreshBookMetadata(bookId: 5, fields: [YEAR]) {
+                    book {
+                        id
+                        title
+                        year
+                        pages
+                        imageUrl
+                    }
+                    changes {
+                        field
+                        oldValue
+                        newValue
+                    }
+                    skipped {
+                        field
+                        oldValue
+                        newValue
+                    }
+                    error
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    insta::assert_json_snapshot!("refresh_book_metadata_fields", refresh);
+
+    // Editing a field also keeps it from being refreshed.
+    run_request(
+        request(
+            "
+            mutation {
+                updateBook(input: { bookId: 5, pages: 300 }) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+
+    // Only books added from a provider are refreshed.
+    let refresh = run_request(
+        request(
+            "
+            mutation {
+                refreshAllBookMetadata {
+                    book {
+                        id
+                        title
+                        year
+                        pages
+                        imageUrl
+                    }
+                    changes {
+                        field
+                        oldValue
+                        newValue
+                    }
+                    skipped {
+                        field
+                        oldValue
+                        newValue
+                    }
+                    error
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    insta::assert_json_snapshot!("refresh_all_book_metadata", refresh);
+
+    let refresh = run_request(
+        request(
+            "
+            mutation {
+                refreshBookMetadata(bookId: 1) {
+                    changes {
+                        field
+                    }
+                }
+            }
+            ",
+        ),
+        app,
+    )
+    .await;
+    insta::assert_json_snapshot!("refresh_book_metadata_without_provider", refresh);
+}
+
+#[tokio::test]
+async fn test_book_authors() {
+    let app = generate_memory_app(MemoryBookRepository::with_sample_data(), &Config::default())
+        .await
+        .unwrap();
+    let result = run_book_authors(app).await;
+    insta::assert_json_snapshot!("book_authors", result);
+}
+
+#[tokio::test]
+async fn test_merge_authors() {
+    let app = generate_memory_app(MemoryBookRepository::with_sample_data(), &Config::default())
+        .await
+        .unwrap();
+    let result = run_merge_authors(app).await;
+    insta::assert_json_snapshot!("merge_authors", result);
+}
+
+#[tokio::test]
+async fn test_merge_authors_conflict() {
+    let app = generate_memory_app(MemoryBookRepository::with_sample_data(), &Config::default())
+        .await
+        .unwrap();
+    let result = run_merge_authors_conflict(app).await;
+    insta::assert_json_snapshot!("merge_authors_conflict", result);
+}
+
+#[tokio::test]
+async fn test_tags_and_shelves() {
+    let app = generate_memory_app(MemoryBookRepository::with_sample_data(), &Config::default())
+        .await
+        .unwrap();
+    let result = run_tags_and_shelves(app).await;
+    insta::assert_json_snapshot!("tags_and_shelves", result);
+}
+
+#[tokio::test]
+async fn test_series_query() {
+    let app = generate_memory_app(MemoryBookRepository::with_sample_data(), &Config::default())
+        .await
+        .unwrap();
+    let result = run_series(app).await;
+    insta::assert_json_snapshot!("series_query", result);
+}
+
+#[tokio::test]
+async fn test_reading_statuses() {
+    let app = generate_memory_app(MemoryBookRepository::with_sample_data(), &Config::default())
+        .await
+        .unwrap();
+    let result = run_reading_statuses(app).await;
+    insta::assert_json_snapshot!("reading_statuses", result);
+}
+
+#[tokio::test]
+async fn test_status_transitions() {
+    check_status_transitions(&MemoryBookRepository::with_sample_data()).await;
+}
+
+#[tokio::test]
+async fn test_status_history() {
+    let app = generate_memory_app(MemoryBookRepository::with_sample_data(), &Config::default())
+        .await
+        .unwrap();
+    let result = run_status_history(app).await;
+    insta::assert_json_snapshot!("status_history", result);
+}
+
+#[tokio::test]
+async fn test_rereading() {
+    let app = generate_memory_app(MemoryBookRepository::with_sample_data(), &Config::default())
+        .await
+        .unwrap();
+    let result = run_rereading(app).await;
+    insta::assert_json_snapshot!("rereading", result);
+}
+
+#[tokio::test]
+async fn test_log_progress() {
+    check_log_progress(&MemoryBookRepository::with_sample_data()).await;
+}
+
+#[tokio::test]
+async fn test_reading_progress() {
+    let app = generate_memory_app(MemoryBookRepository::with_sample_data(), &Config::default())
+        .await
+        .unwrap();
+    let result = run_reading_progress(app).await;
+    insta::assert_json_snapshot!("reading_progress", result);
+}
+
+#[tokio::test]
+async fn test_parallel_queries_run_concurrently() {
+    // Each store operation takes 100ms, so running 10 `books { notes }` queries (a books lookup
+    // plus a batched notes lookup each) one after another would take at least 2 seconds.
+    const QUERIES: usize = 10;
+    let latency = Duration::from_millis(100);
+
+    let repository = MemoryBookRepository::with_sample_data().with_latency(latency);
+    let app = generate_memory_app(repository, &Config::default())
+        .await
+        .unwrap();
+
+    let book_query = "
+        query {
+          books {
+            id
+            notes {
+              id
+            }
+          }
+        }
+    ";
+
+    let start = Instant::now();
+    let handles: Vec<_> = (0..QUERIES)
+        .map(|_| {
+            let body = Request {
+                query: book_query.to_string(),
+                operation_name: None,
+                variables: None,
+            };
+            tokio::spawn(run_request(body, app.clone()))
+        })
+        .collect();
+    for handle in handles {
+        let result = handle.await.unwrap();
+        assert_eq!(result["data"]["books"].as_array().unwrap().len(), 4);
+    }
+    let elapsed = start.elapsed();
+
+    // Queries overlap with each other, so the whole batch finishes in about two round trips
+    // instead of queueing behind a lock.
+    assert!(
+        elapsed < latency * 5,
+        "{QUERIES} parallel queries took {elapsed:?}"
+    );
+}
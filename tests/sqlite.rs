@@ -0,0 +1,891 @@
+use borges::{config::Config, generate_sqlite_app, repository::SqliteBookRepository};
+use serde_json::{json, Value};
+use sqlx::{Pool, Sqlite};
+
+mod common;
+
+use common::{
+    check_find_library_books, check_log_progress, check_status_transitions,
+    check_update_author_keeps_credits, run_book_authors, run_merge_authors,
+    run_merge_authors_conflict, run_reading_progress, run_reading_statuses, run_request,
+    run_rereading, run_series, run_status_history, run_tags_and_shelves, Request, StubProvider,
+};
+
+async fn _run_request(request_body: Request, pool: Pool<Sqlite>) -> Value {
+    let app = generate_sqlite_app(pool, &Config::default()).await.unwrap();
+    run_request(request_body, app).await
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_book_query(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("book_query");
+
+    let book_query = "
+        query {
+          book(bookId: 1) {
+            id
+            title
+            author
+            year
+            pages
+            status
+            notes {
+              id
+              bookId
+              note
+              page
+            }
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_all_books_query(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("all_books_query");
+
+    let book_query = "
+        query {
+          books {
+            id
+            title
+            author
+            year
+            pages
+            status
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_read_books_query(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("read_books_query");
+
+    let book_query = "
+        query {
+          books(status: READ) {
+            id
+            title
+            author
+            year
+            pages
+            status
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_add_note_mutation(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("add_note_mutation");
+
+    let mutation = "
+        mutation {
+          addNote(input: {bookId: 1, note: \"new note!\", page: 3}) {
+            note {
+              id
+              bookId
+              note
+              page
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_invalid_book_id_note_mutation(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("invalid_book_id_note_mutation");
+
+    let mutation = "
+        mutation {
+          addNote(input: {bookId: 9999, note: \"new note!\", page: 3}) {
+            note {
+              id
+              bookId
+              note
+              page
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_invalid_book_id_status_mutation(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("invalid_book_id_status_mutation");
+
+    let mutation = "
+        mutation {
+          updateBookStatus(input: {bookId: 999, status: READ}) {
+            book {
+              id
+              title
+              status
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_book_status_mutation(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("book_status_mutation");
+
+    let mutation = "
+        mutation {
+          updateBookStatus(input: {bookId: 2, status: READ}) {
+            book {
+              id
+              title
+              status
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_duplicate_add_book_mutation(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("duplicate_add_book_mutation");
+
+    let mutation = "
+        mutation {
+          addBook(input: {title: \"Blood Meridian\", author: \"Cormac McCarthy\", year: 1985, pages: 351}) {
+            book {
+              id
+              title
+            }
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_delete_book_mutation(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("delete_book_mutation");
+
+    let mutation = "
+        mutation {
+          deleteBook(bookId: 2) {
+            bookId
+            success
+          }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool.clone()).await;
+    insta::assert_json_snapshot!(result);
+
+    let notes: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM note WHERE book_id = 2")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(notes, 0);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_books_connection_after_cursor_query(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("books_connection_after_cursor_query");
+
+    let book_query = "
+        query {
+          booksConnection(first: 2, after: \"Mg\") {
+            edges {
+              cursor
+              node {
+                id
+                title
+              }
+            }
+            pageInfo {
+              hasPreviousPage
+              hasNextPage
+              startCursor
+              endCursor
+            }
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_books_connection_last_page_query(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("books_connection_last_page_query");
+
+    let book_query = "
+        query {
+          booksConnection(status: UNREAD, last: 2) {
+            edges {
+              cursor
+              node {
+                id
+                title
+                status
+              }
+            }
+            pageInfo {
+              hasPreviousPage
+              hasNextPage
+            }
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_filtered_sorted_books_query(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("filtered_sorted_books_query");
+
+    let book_query = "
+        query {
+          books(filter: {minYear: 1980, maxPages: 500}, sort: TITLE, direction: DESCENDING) {
+            id
+            title
+            year
+            pages
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_year_sorted_books_query(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("year_sorted_books_query");
+
+    let book_query = "
+        query {
+          books(sort: YEAR) {
+            id
+            title
+            year
+          }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_library_search_notes_query(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("library_search_notes_query");
+
+    let book_query = "
+        query {
+            librarySearch(query: \"dolor\") {
+                matched
+                snippet
+                book {
+                    title
+                }
+                note {
+                    id
+                    page
+                }
+            }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_library_search_books_query(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("library_search_books_query");
+
+    let book_query = "
+        query {
+            librarySearch(query: \"borges fictions\") {
+                matched
+                snippet
+                book {
+                    id
+                    title
+                    author
+                }
+            }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_find_library_books(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    check_find_library_books(&SqliteBookRepository::new(pool)).await;
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_update_author_keeps_credits(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    check_update_author_keeps_credits(&SqliteBookRepository::new(pool)).await;
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_add_book_isbn_mutation(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("add_book_isbn_mutation");
+
+    let mutation = "
+        mutation {
+            addBook(input: { title: \"Gravity's Rainbow (Penguin Classics)\", author: \"Thomas Pynchon\", year: 2006, pages: 776, isbn10: \"0-14-303994-6\" }) {
+                book {
+                    title
+                    isbn10
+                    isbn13
+                }
+                success
+            }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_add_book_duplicate_isbn_mutation(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("add_book_duplicate_isbn_mutation");
+
+    let mutation = "
+        mutation {
+            addBook(input: { title: \"Gravity's Rainbow (Penguin Classics)\", author: \"Thomas Pynchon\", year: 2006, pages: 776, isbn13: \"978-0-14-303994-5\" }) {
+                success
+            }
+        }
+    "
+    .to_string();
+
+    let body = Request {
+        query: mutation,
+        operation_name: None,
+        variables: None,
+    };
+
+    sqlx::query("UPDATE book SET isbn_10 = '0143039946', isbn_13 = '9780143039945' WHERE id = 2")
+        .execute(&pool)
+        .await?;
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_book_by_isbn_query(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("book_by_isbn_query");
+
+    let book_query = "
+        query {
+            book(isbn: \"0-14-303994-6\") {
+                id
+                title
+                isbn10
+                isbn13
+            }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: book_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    sqlx::query("UPDATE book SET isbn_10 = '0143039946', isbn_13 = '9780143039945' WHERE id = 2")
+        .execute(&pool)
+        .await?;
+
+    let result = _run_request(body, pool).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_persistent_metadata_cache(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("persistent_metadata_cache");
+
+    let stub = StubProvider::start().await;
+    let config = Config {
+        metadata_cache_persistent: true,
+        ..stub.config()
+    };
+    let search = || Request {
+        query: "
+            query {
+                search(query: \"gravity's rainbow\") {
+                    id
+                    title
+                }
+            }
+        "
+        .to_string(),
+        operation_name: None,
+        variables: None,
+    };
+
+    let app = generate_sqlite_app(pool.clone(), &config).await.unwrap();
+    let first = run_request(search(), app).await;
+
+    // A new app, like after a restart, answers the search from the database.
+    let app = generate_sqlite_app(pool, &config).await.unwrap();
+    let second = run_request(search(), app.clone()).await;
+    assert_eq!(first, second);
+    assert_eq!(1, stub.requests());
+
+    let stats_query = "
+        query {
+            metadataCacheStats {
+                hits
+                persistentHits
+                misses
+                entries
+            }
+        }
+    "
+    .to_string();
+    let body = Request {
+        query: stats_query,
+        operation_name: None,
+        variables: None,
+    };
+
+    let result = run_request(body, app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_refresh_all_book_metadata(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("refresh_all_book_metadata");
+
+    let stub = StubProvider::start().await;
+    let app = generate_sqlite_app(pool.clone(), &stub.config())
+        .await
+        .unwrap();
+    let request = |query: &str| Request {
+        query: query.to_string(),
+        operation_name: None,
+        variables: None,
+    };
+
+    run_request(
+        request(
+            "
+            mutation {
+                addExternalBook(input: { provider: GOOGLE_BOOKS, externalId: \"zyTCAlFPjgYC\" }) {
+                    success
+                }
+            }
+            ",
+        ),
+        app.clone(),
+    )
+    .await;
+    // The provider's data has changed since the book was added.
+    sqlx::query("UPDATE book SET pages = 200, image_url = NULL WHERE title = 'The Google Story'")
+        .execute(&pool)
+        .await?;
+
+    let result = run_request(
+        request(
+            "
+            mutation {
+                refreshAllBookMetadata {
+                    book {
+                        id
+                        title
+                        year
+                        pages
+                        imageUrl
+                    }
+                    changes {
+                        field
+                        oldValue
+                        newValue
+                    }
+                    skipped {
+                        field
+                        oldValue
+                        newValue
+                    }
+                    error
+                }
+            }
+            ",
+        ),
+        app,
+    )
+    .await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_add_book_editions_by_isbn(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("add_book_editions_by_isbn");
+
+    let stub = StubProvider::start().await;
+    let app = generate_sqlite_app(pool, &stub.config()).await.unwrap();
+    let request = |query: String| Request {
+        query,
+        operation_name: None,
+        variables: None,
+    };
+    let add = |isbn: &str| {
+        request(format!(
+            "
+            mutation {{
+                addBookByIsbn(input: {{ provider: GOOGLE_BOOKS, isbn: \"{isbn}\" }}) {{
+                    book {{
+                        id
+                        title
+                        isbn13
+                    }}
+                }}
+            }}
+            "
+        ))
+    };
+
+    // The stub finds the sample data's Gravity's Rainbow for every ISBN.
+    run_request(
+        request("mutation { deleteBook(bookId: 2) { success } }".to_string()),
+        app.clone(),
+    )
+    .await;
+    run_request(add("9780143039945"), app.clone()).await;
+    run_request(
+        request(
+            "
+            mutation {
+                updateBook(input: { bookId: 5, title: \"Gravity's Rainbow (Penguin Classics)\" }) {
+                    success
+                }
+            }
+            "
+            .to_string(),
+        ),
+        app.clone(),
+    )
+    .await;
+
+    // Both editions are the same provider result, but each is keyed on its own ISBN.
+    let second_edition = run_request(add("9780099533214"), app.clone()).await;
+    let refresh = run_request(
+        request(
+            "
+            mutation {
+                refreshBookMetadata(bookId: 6) {
+                    book {
+                        id
+                        isbn13
+                    }
+                    error
+                }
+            }
+            "
+            .to_string(),
+        ),
+        app,
+    )
+    .await;
+    insta::assert_json_snapshot!(json!([second_edition, refresh]));
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_book_authors(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("book_authors");
+
+    let app = generate_sqlite_app(pool, &Config::default()).await.unwrap();
+    let result = run_book_authors(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_merge_authors(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("merge_authors");
+
+    let app = generate_sqlite_app(pool, &Config::default()).await.unwrap();
+    let result = run_merge_authors(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_merge_authors_conflict(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("merge_authors_conflict");
+
+    let app = generate_sqlite_app(pool, &Config::default()).await.unwrap();
+    let result = run_merge_authors_conflict(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_tags_and_shelves(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("tags_and_shelves");
+
+    let app = generate_sqlite_app(pool, &Config::default()).await.unwrap();
+    let result = run_tags_and_shelves(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_series_query(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("series_query");
+
+    let app = generate_sqlite_app(pool, &Config::default()).await.unwrap();
+    let result = run_series(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_reading_statuses(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("reading_statuses");
+
+    let app = generate_sqlite_app(pool, &Config::default()).await.unwrap();
+    let result = run_reading_statuses(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_status_transitions(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    check_status_transitions(&SqliteBookRepository::new(pool)).await;
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_status_history(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("status_history");
+
+    let app = generate_sqlite_app(pool, &Config::default()).await.unwrap();
+    let result = run_status_history(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_rereading(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("rereading");
+
+    let app = generate_sqlite_app(pool, &Config::default()).await.unwrap();
+    let result = run_rereading(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_log_progress(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    check_log_progress(&SqliteBookRepository::new(pool)).await;
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./sqlite_migrations")]
+async fn test_reading_progress(pool: Pool<Sqlite>) -> sqlx::Result<()> {
+    set_snapshot_suffix!("reading_progress");
+
+    let app = generate_sqlite_app(pool, &Config::default()).await.unwrap();
+    let result = run_reading_progress(app).await;
+    insta::assert_json_snapshot!(result);
+
+    Ok(())
+}